@@ -0,0 +1,19 @@
+#!/bin/sh
+# Build with the embed-inputs feature and check every day runs from an empty directory,
+# with no input/ next to it and no AOC_INPUT_KEY set.
+set -eu
+
+cd "$(dirname "$0")/.."
+cargo build --release --features embed-inputs
+
+bin="$(pwd)/target/release"
+empty="$(mktemp -d)"
+trap 'rm -rf "$empty"' EXIT
+
+for day in $(seq -w 1 11); do
+    (cd "$empty" && env -u AOC_INPUT_KEY "$bin/day$day" >/dev/null) || {
+        echo "day$day failed without input/" >&2
+        exit 1
+    }
+done
+echo "all days ran from the embedded inputs"
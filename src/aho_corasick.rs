@@ -0,0 +1,156 @@
+//! Aho–Corasick automaton for finding every occurrence of a fixed set of patterns in one
+//! pass over the text, however many patterns there are.
+
+/// A match of the pattern carrying `value`, spanning `haystack[start..end]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'a, V> {
+    pub start: usize,
+    pub end: usize,
+    pub value: &'a V,
+}
+
+/// Patterns compiled into a byte-level DFA: failure links are folded into the transition
+/// table up front, so scanning is one table lookup per byte.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick<V> {
+    transitions: Vec<[u32; 256]>,
+    /// Patterns ending at each state, including those reached through failure links.
+    outputs: Vec<Vec<usize>>,
+    /// Length and value of each pattern.
+    patterns: Vec<(usize, V)>,
+}
+
+const NONE: u32 = u32::MAX;
+
+impl<V> AhoCorasick<V> {
+    /// Build the automaton from pattern → value pairs. Empty patterns never match.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = (P, V)>) -> Self {
+        let mut automaton = AhoCorasick {
+            transitions: vec![[NONE; 256]],
+            outputs: vec![vec![]],
+            patterns: vec![],
+        };
+
+        for (pattern, value) in patterns {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let mut state = 0;
+            for &byte in pattern {
+                if automaton.transitions[state][byte as usize] == NONE {
+                    automaton.transitions[state][byte as usize] =
+                        automaton.transitions.len() as u32;
+                    automaton.transitions.push([NONE; 256]);
+                    automaton.outputs.push(vec![]);
+                }
+                state = automaton.transitions[state][byte as usize] as usize;
+            }
+
+            automaton.outputs[state].push(automaton.patterns.len());
+            automaton.patterns.push((pattern.len(), value));
+        }
+
+        automaton.link();
+        automaton
+    }
+
+    /// Breadth first from the root, so every state's failure target is finished before
+    /// the state itself: missing transitions are borrowed from the failure target and
+    /// its outputs are inherited.
+    fn link(&mut self) {
+        let mut fail = vec![0; self.transitions.len()];
+        let mut queue = std::collections::VecDeque::new();
+
+        for byte in 0..256 {
+            match self.transitions[0][byte] {
+                NONE => self.transitions[0][byte] = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                let fallback = self.transitions[fail[state]][byte];
+                match self.transitions[state][byte] {
+                    NONE => self.transitions[state][byte] = fallback,
+                    child => {
+                        let child = child as usize;
+                        fail[child] = fallback as usize;
+                        let inherited = self.outputs[fail[child]].clone();
+                        self.outputs[child].extend(inherited);
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every match, overlapping ones included, in order of where they end. Matches
+    /// ending at the same byte come longest first.
+    pub fn find_overlapping<'a>(
+        &'a self,
+        haystack: &'a [u8],
+    ) -> impl Iterator<Item = Match<'a, V>> + 'a {
+        self.find_overlapping_in(haystack.iter().copied())
+    }
+
+    /// Like `find_overlapping`, over bytes that needn't be in a slice, such as a line
+    /// read backwards. Positions count bytes from the start of the iterator.
+    pub fn find_overlapping_in<'a>(
+        &'a self,
+        haystack: impl IntoIterator<Item = u8> + 'a,
+    ) -> impl Iterator<Item = Match<'a, V>> + 'a {
+        haystack
+            .into_iter()
+            .scan(0, move |state, byte| {
+                *state = self.transitions[*state][byte as usize] as usize;
+                Some(*state)
+            })
+            .enumerate()
+            .flat_map(move |(i, state)| {
+                self.outputs[state].iter().map(move |&pattern| {
+                    let (len, value) = &self.patterns[pattern];
+                    Match {
+                        start: i + 1 - len,
+                        end: i + 1,
+                        value,
+                    }
+                })
+            })
+    }
+}
+
+#[test]
+fn test_find_overlapping() {
+    let automaton = AhoCorasick::new([("he", 1), ("she", 2), ("his", 3), ("hers", 4)]);
+
+    let found = automaton
+        .find_overlapping(b"ushers")
+        .map(|m| (m.start, m.end, *m.value))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![(1, 4, 2), (2, 4, 1), (2, 6, 4)]);
+
+    assert_eq!(automaton.find_overlapping(b"").count(), 0);
+
+    // Reversed patterns over the reversed text find the same matches from the other end.
+    let reversed = AhoCorasick::new([("eh", 1), ("ehs", 2), ("sih", 3), ("sreh", 4)]);
+    let found = reversed
+        .find_overlapping_in(b"ushers".iter().rev().copied())
+        .map(|m| (m.start, m.end, *m.value))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![(0, 4, 4), (2, 4, 1), (2, 5, 2)]);
+    assert_eq!(automaton.find_overlapping("h€s".as_bytes()).count(), 0);
+}
+
+#[test]
+fn test_shared_prefixes_and_suffixes() {
+    let automaton = AhoCorasick::new([("eight", 8), ("two", 2), ("one", 1), ("", 0), ("t", 10)]);
+
+    let found = automaton
+        .find_overlapping(b"eightwone")
+        .map(|m| (m.start, *m.value))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![(0, 8), (4, 10), (4, 2), (6, 1)]);
+}
@@ -0,0 +1,91 @@
+//! Puzzle answers are accumulated in a `u128` with every step checked, so an oversized
+//! input produces an error instead of a silently wrapped answer in release builds.
+
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Answer(pub u128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Answer overflowed u128")
+    }
+}
+
+impl Error for Overflow {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Answer {
+                    Answer(n as u128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(u8, u16, u32, u64, usize, u128);
+
+impl Answer {
+    pub const ZERO: Answer = Answer(0);
+    pub const ONE: Answer = Answer(1);
+
+    pub fn checked_add(self, other: impl Into<Answer>) -> Result<Answer, Overflow> {
+        self.0
+            .checked_add(other.into().0)
+            .map(Answer)
+            .ok_or(Overflow)
+    }
+
+    pub fn checked_mul(self, other: impl Into<Answer>) -> Result<Answer, Overflow> {
+        self.0
+            .checked_mul(other.into().0)
+            .map(Answer)
+            .ok_or(Overflow)
+    }
+
+    pub fn checked_pow(self, exp: u32) -> Result<Answer, Overflow> {
+        self.0.checked_pow(exp).map(Answer).ok_or(Overflow)
+    }
+}
+
+/// Sum anything that widens into an `Answer`, failing on overflow.
+pub fn sum<T: Into<Answer>>(items: impl IntoIterator<Item = T>) -> Result<Answer, Overflow> {
+    items
+        .into_iter()
+        .try_fold(Answer::ZERO, |total, n| total.checked_add(n))
+}
+
+/// Multiply anything that widens into an `Answer`, failing on overflow.
+pub fn product<T: Into<Answer>>(items: impl IntoIterator<Item = T>) -> Result<Answer, Overflow> {
+    items
+        .into_iter()
+        .try_fold(Answer::ONE, |total, n| total.checked_mul(n))
+}
+
+#[test]
+fn test_sum_and_product() {
+    assert_eq!(sum([1u32, 2, 3]), Ok(Answer(6)));
+    assert_eq!(sum(Vec::<u32>::new()), Ok(Answer::ZERO));
+    assert_eq!(product([2usize, 3, 4]), Ok(Answer(24)));
+
+    // Past where u32 and u64 would have wrapped.
+    assert_eq!(sum([u32::MAX, 1]), Ok(Answer(1 << 32)));
+    assert_eq!(product([u64::MAX, 2]), Ok(Answer(u64::MAX as u128 * 2)));
+
+    assert_eq!(sum([u128::MAX, 1]), Err(Overflow));
+    assert_eq!(product([u128::MAX, 2]), Err(Overflow));
+    assert_eq!(Answer(2).checked_pow(128), Err(Overflow));
+    assert_eq!(Answer(2).to_string(), "2");
+}
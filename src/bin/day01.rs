@@ -1,68 +1,875 @@
-fn parse_spelled_numbers(input: &str) -> Vec<u32> {
-    let number_words = [
-        ("one", 1),
-        ("1", 1),
-        ("two", 2),
-        ("2", 2),
-        ("three", 3),
-        ("3", 3),
-        ("four", 4),
-        ("4", 4),
-        ("five", 5),
-        ("5", 5),
-        ("six", 6),
-        ("6", 6),
-        ("seven", 7),
-        ("7", 7),
-        ("eight", 8),
-        ("8", 8),
-        ("nine", 9),
-        ("9", 9),
-    ];
+use std::{
+    cmp::Reverse,
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+};
 
+use common::{
+    aho_corasick::{AhoCorasick, Match},
+    answer::Answer,
+    parse::{self, ParseError},
+};
+
+const USAGE: &str = "usage: day01 [--words english|spanish|german|<file>] [--zero] [--teens]
+             [--non-ascii skip|report] [--policy overlapping|consuming] [--search all|ends]
+             [--explain all|differing]";
+
+/// Spelled out numbers from zero to nineteen for each built-in language.
+const LANGUAGES: [(&str, [&str; 20]); 3] = [
+    (
+        "english",
+        [
+            "zero",
+            "one",
+            "two",
+            "three",
+            "four",
+            "five",
+            "six",
+            "seven",
+            "eight",
+            "nine",
+            "ten",
+            "eleven",
+            "twelve",
+            "thirteen",
+            "fourteen",
+            "fifteen",
+            "sixteen",
+            "seventeen",
+            "eighteen",
+            "nineteen",
+        ],
+    ),
+    (
+        "spanish",
+        [
+            "cero",
+            "uno",
+            "dos",
+            "tres",
+            "cuatro",
+            "cinco",
+            "seis",
+            "siete",
+            "ocho",
+            "nueve",
+            "diez",
+            "once",
+            "doce",
+            "trece",
+            "catorce",
+            "quince",
+            "dieciséis",
+            "diecisiete",
+            "dieciocho",
+            "diecinueve",
+        ],
+    ),
+    (
+        "german",
+        [
+            "null",
+            "eins",
+            "zwei",
+            "drei",
+            "vier",
+            "fünf",
+            "sechs",
+            "sieben",
+            "acht",
+            "neun",
+            "zehn",
+            "elf",
+            "zwölf",
+            "dreizehn",
+            "vierzehn",
+            "fünfzehn",
+            "sechzehn",
+            "siebzehn",
+            "achtzehn",
+            "neunzehn",
+        ],
+    ),
+];
+
+/// The words part 2 recognises as numbers. Digits are always recognised on top of these,
+/// and `0` only when some word means zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NumberWords {
+    words: Vec<(String, u32)>,
+}
+
+impl Default for NumberWords {
+    /// The puzzle's own table, English one to nine.
+    fn default() -> Self {
+        NumberWords::named("english", false, false).expect("english is built in")
+    }
+}
+
+impl NumberWords {
+    /// A built-in table, one to nine plus optionally zero and ten to nineteen.
+    fn named(language: &str, zero: bool, teens: bool) -> Result<NumberWords, Box<dyn Error>> {
+        let (_, words) = LANGUAGES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            .ok_or_else(|| format!("Unknown language: {}", language))?;
+
+        let from = if zero { 0 } else { 1 };
+        let to = if teens { 20 } else { 10 };
+        let words = (from..to)
+            .map(|n| (words[n].to_string(), n as u32))
+            .collect();
+
+        Ok(NumberWords { words })
+    }
+
+    /// One `word: value` per line. Blank lines and lines starting with `#` are skipped.
+    fn parse(text: &str) -> Result<NumberWords, ParseError> {
+        let mut words = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let (word, value) = parse::key_value(line).map_err(|e| e.on_line(i + 1))?;
+            if word.is_empty() {
+                let at = line.len() - line.trim_start().len();
+                return Err(ParseError::new(at, "expected a word").on_line(i + 1));
+            }
+            let value = parse::Cursor::new(value)
+                .int::<u32>()
+                .map_err(|e| e.within(line, value).on_line(i + 1))?;
+
+            words.push((word.to_string(), value));
+        }
+
+        Ok(NumberWords { words })
+    }
+
+    /// `source` is either a built-in language or a file to `parse`.
+    fn load(source: &str, zero: bool, teens: bool) -> Result<NumberWords, Box<dyn Error>> {
+        if LANGUAGES
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(source))
+        {
+            return NumberWords::named(source, zero, teens);
+        }
+        if zero || teens {
+            return Err("--zero and --teens only apply to built-in languages".into());
+        }
+
+        let text = std::fs::read_to_string(source)
+            .map_err(|e| format!("Could not read number words from {}: {}", source, e))?;
+        Ok(NumberWords::parse(&text)?)
+    }
+
+    /// Every pattern to look for with its value, digits included.
+    fn patterns(&self) -> Vec<(String, u32)> {
+        let zero = self.words.iter().any(|(_, value)| *value == 0);
+        let digits = (u32::from(!zero)..10).map(|d| (d.to_string(), d));
+
+        self.words.iter().cloned().chain(digits).collect()
+    }
+
+    fn automaton(&self) -> AhoCorasick<u32> {
+        AhoCorasick::new(self.patterns())
+    }
+}
+
+#[test]
+fn test_number_words() {
+    let words = NumberWords::default();
+    assert_eq!(words.words.len(), 9);
+    assert_eq!(words.patterns().len(), 18);
+
+    let spanish = NumberWords::named("Spanish", true, true).unwrap();
+    assert_eq!(spanish.words[0], ("cero".to_string(), 0));
+    assert_eq!(spanish.words[16], ("dieciséis".to_string(), 16));
+    assert_eq!(spanish.patterns().len(), 30);
+    assert!(NumberWords::named("klingon", false, false).is_err());
+
+    let parsed = NumberWords::parse("# roman\nI: 1\n\n  V :5\nX: 10\n").unwrap();
+    assert_eq!(
+        parsed.words,
+        vec![
+            ("I".to_string(), 1),
+            ("V".to_string(), 5),
+            ("X".to_string(), 10)
+        ]
+    );
+    assert_eq!(
+        NumberWords::parse("I: 1\nV: five").unwrap_err().to_string(),
+        "line 2, column 4: expected an integer"
+    );
+    assert_eq!(
+        NumberWords::parse("I: 1\n  : 1").unwrap_err().to_string(),
+        "line 2, column 3: expected a word"
+    );
+    assert!(NumberWords::load("german", true, false).is_ok());
+    assert!(NumberWords::load("/no/such/words.txt", false, false).is_err());
+}
+
+/// How number words sharing letters count. Overlapping reads "eightwo" as 8 then 2,
+/// consuming lets "eight" use up the "t" so only the 8 is left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Policy {
+    #[default]
+    Overlapping,
+    Consuming,
+}
+
+/// Tries every word at every offset, kept as the reference for `parse_spelled_numbers`.
+#[cfg(test)]
+fn parse_spelled_numbers_naive(
+    patterns: &[(String, u32)],
+    input: &str,
+    policy: Policy,
+) -> Vec<u32> {
     let mut out = vec![];
 
-    for i in 0..input.len() {
-        for (word, number) in number_words {
-            if input[i..].starts_with(word) {
-                out.push(number);
-                break;
+    // Compare bytes so a multi-byte character can't leave us slicing mid-char.
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let longest = patterns
+            .iter()
+            .filter(|(word, _)| bytes[i..].starts_with(word.as_bytes()))
+            .max_by_key(|(word, _)| word.len());
+        match longest {
+            Some((word, number)) => {
+                out.push(*number);
+                i += match policy {
+                    Policy::Overlapping => 1,
+                    Policy::Consuming => word.len(),
+                };
             }
+            None => i += 1,
         }
     }
 
     out
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = common::read_data("day01")?;
+/// The digits and spelled out numbers in `input` in the order they start. Where several
+/// start at the same place the longest wins, so with the teens "seventeen" is 17 rather
+/// than 7.
+fn spelled_matches<'a>(
+    words: &'a AhoCorasick<u32>,
+    input: &'a str,
+    policy: Policy,
+) -> Vec<Match<'a, u32>> {
+    let mut matches = words.find_overlapping(input.as_bytes()).collect::<Vec<_>>();
+    matches.sort_by_key(|m| (m.start, Reverse(m.end)));
+    matches.dedup_by_key(|m| m.start);
+
+    if policy == Policy::Consuming {
+        let mut free = 0;
+        matches.retain(|m| {
+            let keep = m.start >= free;
+            if keep {
+                free = m.end;
+            }
+            keep
+        });
+    }
+
+    matches
+}
+
+fn parse_spelled_numbers(words: &AhoCorasick<u32>, input: &str, policy: Policy) -> Vec<u32> {
+    spelled_matches(words, input, policy)
+        .into_iter()
+        .map(|m| *m.value)
+        .collect()
+}
 
-    let answer_1: u32 = input
-        .iter()
-        .filter_map(|line| {
-            let digits = line.chars().filter(|c| c.is_ascii_digit()).collect::<Vec<_>>();
+#[test]
+fn test_parse_spelled_numbers_differential() {
+    for words in [
+        NumberWords::default(),
+        NumberWords::named("english", true, true).unwrap(),
+        NumberWords::named("german", true, true).unwrap(),
+    ] {
+        let automaton = words.automaton();
+        let patterns = words.patterns();
 
-            let first_digit = digits.first()?;
-            let last_digit = digits.last()?;
+        for seed in 0..20 {
+            let input = common::generate::generate(1, seed, 200).unwrap();
+            for line in common::lines(&input) {
+                for policy in [Policy::Overlapping, Policy::Consuming] {
+                    assert_eq!(
+                        parse_spelled_numbers(&automaton, &line, policy),
+                        parse_spelled_numbers_naive(&patterns, &line, policy),
+                        "{}",
+                        line
+                    );
+                }
+            }
+        }
+    }
 
-            Some(first_digit.to_digit(10)? * 10 + last_digit.to_digit(10)?)
-        })
-        .sum();
+    let teens = NumberWords::named("english", false, true)
+        .unwrap()
+        .automaton();
+    assert_eq!(
+        parse_spelled_numbers(&teens, "seventeenine", Policy::Overlapping),
+        vec![17, 9]
+    );
+    let german = NumberWords::named("german", true, true)
+        .unwrap()
+        .automaton();
+    assert_eq!(
+        parse_spelled_numbers(&german, "xdreizehnull", Policy::Overlapping),
+        vec![13, 10, 0]
+    );
+    assert_eq!(
+        parse_spelled_numbers(&german, "xdreizehnull", Policy::Consuming),
+        vec![13]
+    );
+}
 
-    println!("Answer 1: {}", answer_1);
+/// How part 2 finds the first and last number in a line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Search {
+    /// Find every number with the automaton and take the ends.
+    All,
+    /// Stop at the first match from each end of the line. Consuming matches always need
+    /// the whole line, so this only changes the overlapping search.
+    #[default]
+    Ends,
+}
 
-    let answer_2: u32 = input
-        .iter()
-        .filter_map(|line| {
-            let digits = parse_spelled_numbers(line);
+/// Finds the first and last number in each line for part 2.
+struct Scanner {
+    automaton: AhoCorasick<u32>,
+    /// Every pattern spelled backwards, to find the last match from the end of a line.
+    reversed: AhoCorasick<u32>,
+    /// Length of the longest pattern.
+    longest: usize,
+    policy: Policy,
+    search: Search,
+}
 
-            let first_digit = digits.first()?;
-            let last_digit = digits.last()?;
+impl Scanner {
+    fn new(words: &NumberWords, policy: Policy, search: Search) -> Scanner {
+        let patterns = words.patterns();
+        let longest = patterns
+            .iter()
+            .map(|(word, _)| word.len())
+            .max()
+            .unwrap_or(0);
+        let reversed = patterns
+            .iter()
+            .map(|(word, value)| (word.bytes().rev().collect::<Vec<_>>(), *value));
 
-            Some(first_digit * 10 + last_digit)
-        })
-        .sum();
+        Scanner {
+            automaton: words.automaton(),
+            reversed: AhoCorasick::new(reversed),
+            longest,
+            policy,
+            search,
+        }
+    }
+
+    /// The match starting first, the longest if several start there. Matches come out
+    /// of the automaton by where they end, so once they end more than a pattern length
+    /// past the best start none of the rest can start before it.
+    fn leftmost<'a>(&'a self, bytes: &'a [u8]) -> Option<Match<'a, u32>> {
+        let mut best: Option<Match<u32>> = None;
+
+        for m in self.automaton.find_overlapping(bytes) {
+            if let Some(b) = best {
+                if m.end > b.start + self.longest {
+                    break;
+                }
+                if (m.start, Reverse(m.end)) >= (b.start, Reverse(b.end)) {
+                    continue;
+                }
+            }
+            best = Some(m);
+        }
+
+        best
+    }
+
+    fn first_last(&self, line: &str) -> Option<(u32, u32)> {
+        // Whether a consumed match near the end survives depends on everything before it,
+        // so that policy always walks the whole line forwards.
+        if self.search == Search::All || self.policy == Policy::Consuming {
+            let numbers = parse_spelled_numbers(&self.automaton, line, self.policy);
+            return Some((*numbers.first()?, *numbers.last()?));
+        }
 
+        let bytes = line.as_bytes();
+        let first = self.leftmost(bytes)?;
+        // Backwards, the first match to end is the one starting last in the line, and the
+        // automaton gives the longest of those first.
+        let last = self
+            .reversed
+            .find_overlapping_in(bytes.iter().rev().copied())
+            .next()?;
+
+        Some((*first.value, *last.value))
+    }
+}
+
+#[test]
+fn test_first_last() {
+    let scanner = |policy, search| Scanner::new(&NumberWords::default(), policy, search);
+
+    for search in [Search::All, Search::Ends] {
+        let overlapping = scanner(Policy::Overlapping, search);
+        assert_eq!(overlapping.first_last("twone"), Some((2, 1)));
+        assert_eq!(overlapping.first_last("eightwo"), Some((8, 2)));
+        assert_eq!(overlapping.first_last("xtwonex"), Some((2, 1)));
+        assert_eq!(overlapping.first_last("abc"), None);
+
+        let consuming = scanner(Policy::Consuming, search);
+        assert_eq!(consuming.first_last("twone"), Some((2, 2)));
+        assert_eq!(consuming.first_last("eightwo"), Some((8, 8)));
+        assert_eq!(consuming.first_last("eightwothree"), Some((8, 3)));
+        assert_eq!(consuming.first_last("oneightwo"), Some((1, 2)));
+        assert_eq!(consuming.first_last(""), None);
+    }
+
+    // Both searches agree on every line, whatever the table.
+    for words in [
+        NumberWords::default(),
+        NumberWords::named("english", true, true).unwrap(),
+        NumberWords::named("spanish", true, true).unwrap(),
+    ] {
+        for policy in [Policy::Overlapping, Policy::Consuming] {
+            let all = Scanner::new(&words, policy, Search::All);
+            let ends = Scanner::new(&words, policy, Search::Ends);
+
+            for line in common::lines(&common::generate::generate(1, 3, 500).unwrap()) {
+                assert_eq!(all.first_last(&line), ends.first_last(&line), "{}", line);
+            }
+        }
+    }
+}
+
+/// Not a correctness test, run with
+/// `cargo test --release --bin day01 -- --ignored --nocapture bench`.
+#[test]
+#[ignore]
+fn bench_parse_spelled_numbers() {
+    let input = common::generate::generate(1, 0, 500_000).unwrap();
+    let lines = common::lines(&input);
+    let bytes = lines.iter().map(|line| line.len()).sum::<usize>();
+
+    let time = |name: &str, parse: &dyn Fn(&str) -> usize| {
+        let start = std::time::Instant::now();
+        let found = lines.iter().map(|line| parse(line)).sum::<usize>();
+        let elapsed = start.elapsed();
+        println!(
+            "{:<10} {} lines, {} bytes, {} numbers: {:?} ({:.0} MB/s)",
+            name,
+            lines.len(),
+            bytes,
+            found,
+            elapsed,
+            bytes as f64 / elapsed.as_secs_f64() / 1e6
+        );
+    };
+
+    let words = NumberWords::default();
+    let patterns = words.patterns();
+    time("naive", &|line| {
+        parse_spelled_numbers_naive(&patterns, line, Policy::Overlapping).len()
+    });
+    let automaton = words.automaton();
+    time("automaton", &|line| {
+        parse_spelled_numbers(&automaton, line, Policy::Overlapping).len()
+    });
+    // Only the two ends are found, so this counts them rather than every number.
+    let scanner = Scanner::new(&words, Policy::Overlapping, Search::Ends);
+    time("ends", &|line| {
+        2 * usize::from(scanner.first_last(line).is_some())
+    });
+}
+
+/// The first and last ASCII digit in `line`, searching in from each end. Bytes are
+/// compared directly, which passes over the pieces of multi-byte characters without ever
+/// splitting one.
+fn first_last_digit(line: &str) -> Option<(u32, u32)> {
+    let bytes = line.as_bytes();
+    let first = bytes.iter().find(|b| b.is_ascii_digit())?;
+    let last = bytes.iter().rfind(|b| b.is_ascii_digit())?;
+
+    Some((u32::from(first - b'0'), u32::from(last - b'0')))
+}
+
+/// What to do about characters outside ASCII. They can only ever match number words
+/// spelled with them, so they are safe to pass over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum NonAscii {
+    #[default]
+    Skip,
+    Report,
+}
+
+/// Lines that didn't scan the way a calibration line should, by line number.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Diagnostics {
+    /// Lines without a digit, which add nothing to part 1.
+    no_digit: Vec<usize>,
+    /// Lines without a digit or number word, which add nothing to part 2.
+    no_number: Vec<usize>,
+    /// Each non-ASCII character, with `NonAscii::Report`.
+    non_ascii: Vec<ParseError>,
+}
+
+impl Diagnostics {
+    fn is_empty(&self) -> bool {
+        self.no_digit.is_empty() && self.no_number.is_empty() && self.non_ascii.is_empty()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.no_digit {
+            writeln!(f, "line {}: no digit, adds nothing to part 1", line)?;
+        }
+        for line in &self.no_number {
+            writeln!(f, "line {}: no number, adds nothing to part 2", line)?;
+        }
+        for error in &self.non_ascii {
+            writeln!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Part 2 uses `scanner`, a number bigger than nine still counts as the tens or units
+/// digit so "twelve" alone calibrates to 132.
+fn solve<R: BufRead>(
+    mut lines: common::LineReader<R>,
+    scanner: &Scanner,
+    non_ascii: NonAscii,
+) -> Result<(Answer, Answer, Diagnostics), Box<dyn Error>> {
+    let mut answer_1 = Answer::ZERO;
+    let mut answer_2 = Answer::ZERO;
+    let mut diagnostics = Diagnostics::default();
+
+    while let Some(line) = lines.next_numbered() {
+        let (number, line) = line?;
+
+        if non_ascii == NonAscii::Report {
+            diagnostics
+                .non_ascii
+                .extend(
+                    line.char_indices()
+                        .filter(|(_, c)| !c.is_ascii())
+                        .map(|(pos, c)| {
+                            ParseError::new(pos, format!("non-ASCII {:?}", c)).on_line(number)
+                        }),
+                );
+        }
+
+        match calibration(first_last_digit(line)) {
+            Some(value) => answer_1 = answer_1.checked_add(value)?,
+            None => diagnostics.no_digit.push(number),
+        }
+
+        match calibration(scanner.first_last(line)) {
+            Some(value) => answer_2 = answer_2.checked_add(value)?,
+            None => diagnostics.no_number.push(number),
+        }
+    }
+
+    Ok((answer_1, answer_2, diagnostics))
+}
+
+#[test]
+fn test_diagnostics() {
+    let input = "two1nine\n\nabc\nfive\ncafé7\n";
+    let scanner = Scanner::new(&NumberWords::default(), Policy::Overlapping, Search::Ends);
+
+    let (answer_1, answer_2, diagnostics) = solve(
+        common::LineReader::new(input.as_bytes()),
+        &scanner,
+        NonAscii::Report,
+    )
+    .unwrap();
+    assert_eq!(
+        (answer_1, answer_2),
+        (Answer(11 + 77), Answer(29 + 55 + 77))
+    );
+    assert_eq!(diagnostics.no_digit, vec![3, 4]);
+    assert_eq!(diagnostics.no_number, vec![3]);
+    assert_eq!(
+        diagnostics.to_string(),
+        "line 3: no digit, adds nothing to part 1\n\
+         line 4: no digit, adds nothing to part 1\n\
+         line 3: no number, adds nothing to part 2\n\
+         line 5, column 4: non-ASCII 'é'\n"
+    );
+
+    let (_, _, diagnostics) = solve(
+        common::LineReader::new(input.as_bytes()),
+        &scanner,
+        NonAscii::Skip,
+    )
+    .unwrap();
+    assert!(diagnostics.non_ascii.is_empty());
+
+    // Word values can be any u32, far past what ten times a digit fits in.
+    let words = NumberWords::parse("big: 4000000000").unwrap();
+    let scanner = Scanner::new(&words, Policy::Overlapping, Search::Ends);
+    let (_, answer_2, _) = solve(
+        common::LineReader::new("bigx\n".as_bytes()),
+        &scanner,
+        NonAscii::Skip,
+    )
+    .unwrap();
+    assert_eq!(answer_2, Answer(44_000_000_000));
+    assert_eq!(first_last_digit("é1ö€2x٣"), Some((1, 2)));
+    assert_eq!(first_last_digit("€"), None);
+}
+
+/// A line's calibration value from its first and last number.
+fn calibration(first_last: Option<(u32, u32)>) -> Option<u64> {
+    first_last.map(|(first, last)| u64::from(first) * 10 + u64::from(last))
+}
+
+/// How one line calibrates in each part, for `--explain`.
+struct Explanation<'a> {
+    number: usize,
+    line: &'a str,
+    /// Every digit and number word with its byte offset, under the scanner's policy.
+    tokens: Vec<(usize, &'a str)>,
+    part1: Option<(u32, u32)>,
+    part2: Option<(u32, u32)>,
+}
+
+impl<'a> Explanation<'a> {
+    fn new(scanner: &'a Scanner, number: usize, line: &'a str) -> Explanation<'a> {
+        let tokens = spelled_matches(&scanner.automaton, line, scanner.policy)
+            .into_iter()
+            .map(|m| (m.start, &line[m.start..m.end]))
+            .collect();
+
+        Explanation {
+            number,
+            line,
+            tokens,
+            part1: first_last_digit(line),
+            part2: scanner.first_last(line),
+        }
+    }
+
+    fn differs(&self) -> bool {
+        calibration(self.part1) != calibration(self.part2)
+    }
+}
+
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "line {}: {}", self.number, self.line)?;
+
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|(pos, token)| format!(" {}@{}", token, pos + 1))
+            .collect::<String>();
+        writeln!(f, "  tokens:{}", tokens)?;
+
+        for (part, first_last, missing) in
+            [(1, self.part1, "no digit"), (2, self.part2, "no number")]
+        {
+            match first_last {
+                Some((first, last)) => writeln!(
+                    f,
+                    "  part {}: first {}, last {} -> {}",
+                    part,
+                    first,
+                    last,
+                    u64::from(first) * 10 + u64::from(last)
+                )?,
+                None => writeln!(f, "  part {}: {} -> 0", part, missing)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Explain every line to `out`, or with `only_differing` just the lines whose two parts
+/// calibrate to different values.
+fn explain<R: BufRead>(
+    mut lines: common::LineReader<R>,
+    scanner: &Scanner,
+    only_differing: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    while let Some(line) = lines.next_numbered() {
+        let (number, line) = line?;
+
+        let explanation = Explanation::new(scanner, number, line);
+        if !only_differing || explanation.differs() {
+            write!(out, "{}", explanation)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_explain() {
+    let input = "1abc2\n\nxtwone3four\nsevenine\n";
+    let scanner = Scanner::new(&NumberWords::default(), Policy::Overlapping, Search::Ends);
+
+    let mut out = vec![];
+    explain(
+        common::LineReader::new(input.as_bytes()),
+        &scanner,
+        false,
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "line 1: 1abc2\n  \
+           tokens: 1@1 2@5\n  \
+           part 1: first 1, last 2 -> 12\n  \
+           part 2: first 1, last 2 -> 12\n\
+         line 3: xtwone3four\n  \
+           tokens: two@2 one@4 3@7 four@8\n  \
+           part 1: first 3, last 3 -> 33\n  \
+           part 2: first 2, last 4 -> 24\n\
+         line 4: sevenine\n  \
+           tokens: seven@1 nine@5\n  \
+           part 1: no digit -> 0\n  \
+           part 2: first 7, last 9 -> 79\n"
+    );
+
+    let mut out = vec![];
+    let scanner = Scanner::new(&NumberWords::default(), Policy::Consuming, Search::Ends);
+    explain(
+        common::LineReader::new(input.as_bytes()),
+        &scanner,
+        true,
+        &mut out,
+    )
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(!out.contains("line 1:"));
+    assert!(out.contains("  tokens: two@2 3@7 four@8\n"));
+    assert!(out.contains("line 4:"));
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "two1nine\r\nabcone2threexyz\r\n";
+    let (answer_1, answer_2, diagnostics) = solve(
+        common::LineReader::new(input.as_bytes()),
+        &Scanner::new(&NumberWords::default(), Policy::Overlapping, Search::Ends),
+        NonAscii::Report,
+    )
+    .unwrap();
+
+    assert_eq!((answer_1, answer_2), (Answer(11 + 22), Answer(29 + 13)));
+    assert!(diagnostics.non_ascii.is_empty());
+}
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        parse_spelled_numbers(
+            &NumberWords::default().automaton(),
+            "é1ö€two",
+            Policy::Overlapping
+        ),
+        vec![1, 2]
+    );
+
+    common::robustness::check(1, 50, 200, |input| {
+        solve(
+            common::LineReader::new(input.as_bytes()),
+            &Scanner::new(&NumberWords::default(), Policy::Consuming, Search::Ends),
+            NonAscii::Report,
+        )
+    })
+    .unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mut source = "english";
+    let mut zero = false;
+    let mut teens = false;
+    let mut non_ascii = NonAscii::Skip;
+    let mut policy = Policy::Overlapping;
+    let mut search = Search::Ends;
+    let mut only_differing = None;
+
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--words" => source = flags.next().ok_or("Missing value for --words")?,
+            "--zero" => zero = true,
+            "--teens" => teens = true,
+            "--non-ascii" => {
+                non_ascii = match flags.next().map(|s| s.as_str()) {
+                    Some("skip") => NonAscii::Skip,
+                    Some("report") => NonAscii::Report,
+                    _ => return Err(format!("--non-ascii takes skip or report\n{}", USAGE).into()),
+                }
+            }
+            "--policy" => {
+                policy = match flags.next().map(|s| s.as_str()) {
+                    Some("overlapping") => Policy::Overlapping,
+                    Some("consuming") => Policy::Consuming,
+                    _ => {
+                        return Err(
+                            format!("--policy takes overlapping or consuming\n{}", USAGE).into(),
+                        )
+                    }
+                }
+            }
+            "--search" => {
+                search = match flags.next().map(|s| s.as_str()) {
+                    Some("all") => Search::All,
+                    Some("ends") => Search::Ends,
+                    _ => return Err(format!("--search takes all or ends\n{}", USAGE).into()),
+                }
+            }
+            "--explain" => {
+                only_differing = match flags.next().map(|s| s.as_str()) {
+                    Some("all") => Some(false),
+                    Some("differing") => Some(true),
+                    _ => return Err(format!("--explain takes all or differing\n{}", USAGE).into()),
+                }
+            }
+            _ => return Err(format!("Unknown flag: {}\n{}", flag, USAGE).into()),
+        }
+    }
+    let scanner = Scanner::new(&NumberWords::load(source, zero, teens)?, policy, search);
+
+    if let Some(only_differing) = only_differing {
+        let stdout = io::stdout();
+        let mut out = io::BufWriter::new(stdout.lock());
+        explain(
+            common::LineReader::open("day01")?,
+            &scanner,
+            only_differing,
+            &mut out,
+        )?;
+        out.flush()?;
+    }
+
+    // Stream the lines so multi-gigabyte calibration documents don't need to fit in memory.
+    let (answer_1, answer_2, diagnostics) =
+        solve(common::LineReader::open("day01")?, &scanner, non_ascii)?;
+    if !diagnostics.is_empty() {
+        eprint!("{}", diagnostics);
+    }
+
+    println!("Answer 1: {}", answer_1);
     println!("Answer 2: {}", answer_2);
     Ok(())
 }
@@ -1,8 +1,102 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    error::Error,
+    fmt,
+};
+
+use common::{
+    answer::{self, Answer},
+    parse::{self, Cursor, ParseError},
+    Input,
+};
+
+/// How many cubes of each color were shown. Colors come from the input, any word will
+/// do.
 #[derive(Debug, Eq, PartialEq)]
 struct Hand {
-    red: u32,
-    green: u32,
-    blue: u32,
+    counts: BTreeMap<String, u32>,
+}
+
+impl Hand {
+    /// How many cubes of `color` were shown, none if it wasn't mentioned.
+    fn count(&self, color: &str) -> u32 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+}
+
+impl<'a> FromIterator<(&'a str, u32)> for Hand {
+    fn from_iter<I: IntoIterator<Item = (&'a str, u32)>>(counts: I) -> Self {
+        Hand {
+            counts: counts
+                .into_iter()
+                .map(|(color, count)| (color.to_string(), count))
+                .collect(),
+        }
+    }
+}
+
+const USAGE: &str =
+    "usage: day02 [--strict] [--bag \"12 red, 13 green, 14 blue\" | --bag-file <file>] [--report]
+       day02 [--strict] rank [<bag> | --bag-file <file>]...
+       day02 [--strict] minimal <game id>...
+       day02 [--strict] maxima";
+
+/// The bag part 1 asks about, written like a hand.
+const BAG: &str = "12 red, 13 green, 14 blue";
+
+/// A bag is written like a hand of how many cubes of each color it holds, over one or
+/// more lines. Lines starting with `#` are comments. Bags are always parsed strictly, a
+/// color given twice is a mistake whichever lines it is on.
+fn parse_bag(text: &str) -> Result<Hand, Box<dyn Error>> {
+    let mut counts = BTreeMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut cursor = Cursor::new(line);
+        let hand = parse_hand_strict(&mut cursor)
+            .and_then(|hand| cursor.ws().end().map(|_| hand))
+            .map_err(|e| format!("Invalid bag: {}", e.on_line(i + 1)))?;
+        for (color, count) in hand.counts {
+            if counts.contains_key(&color) {
+                return Err(format!("Invalid bag: line {}: {} given twice", i + 1, color).into());
+            }
+            counts.insert(color, count);
+        }
+    }
+
+    if counts.is_empty() {
+        return Err("Invalid bag: no cubes given".into());
+    }
+    Ok(Hand { counts })
+}
+
+#[test]
+fn test_parse_bag() {
+    assert_eq!(
+        parse_bag(BAG).unwrap(),
+        Hand::from_iter([("red", 12), ("green", 13), ("blue", 14)])
+    );
+    assert_eq!(
+        parse_bag("# the big bag\n20 red\n 2 gold\n\n").unwrap(),
+        Hand::from_iter([("red", 20), ("gold", 2)])
+    );
+    assert!(parse_bag("20 red,\n2 gold").is_err());
+    assert_eq!(
+        parse_bag("20 red\n2 go ld").unwrap_err().to_string(),
+        "Invalid bag: line 2, column 6: unexpected 'l'"
+    );
+    assert_eq!(
+        parse_bag("12 red, 3 red").unwrap_err().to_string(),
+        "Invalid bag: line 1, column 11: red given twice in one hand"
+    );
+    assert_eq!(
+        parse_bag("12 red\n# more\n3 red").unwrap_err().to_string(),
+        "Invalid bag: line 3: red given twice"
+    );
+    assert!(parse_bag("").is_err());
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -11,71 +105,85 @@ struct Game {
     hands: Vec<Hand>,
 }
 
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = self
+            .counts
+            .iter()
+            .map(|(color, count)| format!("{} {}", count, color))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", shown.join(", "))
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hands = self.hands.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+        write!(f, "Game {}: {}", self.id, hands.join("; "))
+    }
+}
+
+/// Given "3 blue", return the count and the color.
+fn parse_cubes(text: &str) -> Result<(u32, &str), ParseError> {
+    let mut cursor = Cursor::new(text);
+    let count = cursor.int()?;
+    let color = cursor.ws().word()?;
+    cursor.end()?;
+
+    Ok((count, color))
+}
+
 // Given "2 red, 2 green" or "1 red, 1 green, 2 blue"
 // Return a struct representing the hand.
-fn parse_hand(hand_line: &str) -> Option<Hand> {
-    let mut red = 0;
-    let mut green = 0;
-    let mut blue = 0;
-
-    for part in hand_line.split(", ") {
-        let mut parts = part.split_whitespace();
-        let number = parts.next()?.parse::<u32>().ok()?;
-        let color = parts.next()?;
-
-        match color {
-            "red" => red += number,
-            "green" => green += number,
-            "blue" => blue += number,
-            _ => return None,
-        }
+fn parse_hand(hand_line: &str) -> Result<Hand, ParseError> {
+    let mut counts = BTreeMap::new();
+
+    for part in hand_line.split(',') {
+        let part = part.trim();
+        let (number, color) = parse_cubes(part).map_err(|e| e.within(hand_line, part))?;
+
+        let total = counts.entry(color.to_string()).or_insert(0u32);
+        *total = total.checked_add(number).ok_or_else(|| {
+            ParseError::new(0, format!("too many {} cubes", color)).within(hand_line, part)
+        })?;
     }
 
-    Some(Hand { red, green, blue })
+    Ok(Hand { counts })
 }
 
 #[test]
 fn test_parse_hand() {
     let hand = parse_hand("2 red, 2 green").unwrap();
-    let expected = Hand {
-        red: 2,
-        green: 2,
-        blue: 0,
-    };
+    let expected = Hand::from_iter([("red", 2), ("green", 2)]);
     assert_eq!(hand, expected);
+    assert_eq!(hand.count("blue"), 0);
 
     let hand = parse_hand("1 red, 1 green, 2 blue").unwrap();
-    let expected = Hand {
-        red: 1,
-        green: 1,
-        blue: 2,
-    };
+    let expected = Hand::from_iter([("red", 1), ("green", 1), ("blue", 2)]);
     assert_eq!(hand, expected);
+
+    let hand = parse_hand("3 ultraviolet, 1 red").unwrap();
+    assert_eq!(hand.count("ultraviolet"), 3);
+    assert_eq!(
+        parse_hand("1 red, 3 ultra violet").unwrap_err().to_string(),
+        "column 15: unexpected ' '"
+    );
 }
 
 /// Given
 /// Game 1: 2 red, 2 green; 1 red, 1 green, 2 blue; 3 blue, 3 red, 3 green; 1 blue, 3 green, 7 red; 5 red, 3 green, 1 blue
 /// Return a struct representing the game.
-fn parse_game(line: &str) -> Option<Game> {
-    let id = line
-        .split(':')
-        .next()?
-        .split_whitespace()
-        .nth(1)?
-        .parse::<u32>()
-        .ok()?;
-
-    let hands = line
-        .split(':')
-        .nth(1)?
+fn parse_game(line: &str) -> Result<Game, ParseError> {
+    let (_, id, hands) = parse::labelled::<u32>(line)?;
+
+    // Leniently, a hand that doesn't parse is left out rather than failing the game.
+    let hands = hands
         .split(';')
-        .filter_map(|hand| {
-            let hand = hand.trim();
-            parse_hand(hand)
-        })
+        .filter_map(|hand| parse_hand(hand.trim()).ok())
         .collect::<Vec<_>>();
 
-    Some(Game { id, hands })
+    Ok(Game { id, hands })
 }
 
 #[test]
@@ -86,57 +194,570 @@ fn test_parse_game() {
     let expected = Game {
         id: 1,
         hands: vec![
-            Hand {
-                red: 2,
-                green: 2,
-                blue: 0,
-            },
-            Hand {
-                red: 1,
-                green: 1,
-                blue: 2,
-            },
-            Hand {
-                red: 3,
-                green: 3,
-                blue: 3,
-            },
+            Hand::from_iter([("red", 2), ("green", 2)]),
+            Hand::from_iter([("red", 1), ("green", 1), ("blue", 2)]),
+            Hand::from_iter([("red", 3), ("green", 3), ("blue", 3)]),
         ],
     };
 
     assert_eq!(game, expected);
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = common::read_data("day02")?;
+/// How closely the input has to follow the puzzle's format. Lenient adds up a color
+/// given twice in a hand, and drops hands and games it can't parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Lenient,
+    Strict,
+}
+
+/// A hand in a strict game, up to the next `;` or the end of the line.
+fn parse_hand_strict(cursor: &mut Cursor) -> Result<Hand, ParseError> {
+    let mut counts = BTreeMap::new();
+
+    cursor.ws();
+    if cursor.is_empty() || cursor.rest().starts_with(';') {
+        return Err(cursor.error("empty hand"));
+    }
+
+    loop {
+        let count = cursor.ws().int::<u32>()?;
+        let at = cursor.ws().pos();
+        let color = cursor.word()?;
+        if counts.insert(color.to_string(), count).is_some() {
+            return Err(ParseError::new(
+                at,
+                format!("{} given twice in one hand", color),
+            ));
+        }
+
+        if cursor.optional(|c| c.ws().tag(",")).is_none() {
+            return Ok(Hand { counts });
+        }
+    }
+}
+
+/// A game that must start with "Game", along with where its id starts.
+fn parse_game_strict(line: &str) -> Result<(Game, usize), ParseError> {
+    let mut cursor = Cursor::new(line);
+
+    let at = cursor.ws().pos();
+    let label = cursor.word()?;
+    if label != "Game" {
+        return Err(ParseError::new(
+            at,
+            format!("expected \"Game\", found {:?}", label),
+        ));
+    }
+    let id_at = cursor.ws().pos();
+    let id = cursor.int()?;
+    cursor.ws().tag(":")?;
+
+    let mut hands = vec![];
+    loop {
+        hands.push(parse_hand_strict(&mut cursor)?);
+        if cursor.ws().is_empty() {
+            return Ok((Game { id, hands }, id_at));
+        }
+        cursor.tag(";")?;
+    }
+}
+
+#[test]
+fn test_parse_game_strict() {
+    let (game, _) = parse_game_strict("Game 1: 2 red, 2 green; 1 blue").unwrap();
+    assert_eq!(game, parse_game("Game 1: 2 red, 2 green; 1 blue").unwrap());
+
+    let error = |line| parse_game_strict(line).unwrap_err().to_string();
+    assert_eq!(
+        error("Game 1: 2 red, 3 red"),
+        "column 18: red given twice in one hand"
+    );
+    assert_eq!(
+        error("Round 1: 2 red"),
+        "column 1: expected \"Game\", found \"Round\""
+    );
+    assert_eq!(error("Game 1: 2 red; ; 1 blue"), "column 16: empty hand");
+    assert_eq!(error("Game 1:"), "column 8: empty hand");
+    assert_eq!(error("Game 1: 2 red;"), "column 15: empty hand");
+    assert_eq!(error("Game 1: 2 red 1 blue"), "column 15: expected \";\"");
+    assert_eq!(error("Game x: 2 red"), "column 6: expected an integer");
+
+    // Lenient parsing lets all of these through one way or another.
+    assert_eq!(
+        parse_game("Game 1: 2 red, 3 red").unwrap().hands,
+        vec![Hand::from_iter([("red", 5)])]
+    );
+    assert!(parse_game("Round 1: 2 red").is_ok());
+    assert_eq!(
+        parse_game("Game 1: 2 red; ; 1 blue").unwrap().hands.len(),
+        2
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    for seed in 0..20 {
+        for line in common::lines(&common::generate::generate(2, seed, 50).unwrap()) {
+            let game = parse_game(&line).unwrap();
+            let printed = game.to_string();
+
+            assert_eq!(parse_game(&printed), Ok(game));
+            assert_eq!(parse_game(&printed).unwrap().to_string(), printed);
+        }
+    }
+
+    // Hand-edited spacing and colour order come out canonical.
+    let game = parse_game("Game  3:  4 blue,  1 red ; 0 green").unwrap();
+    assert_eq!(game.to_string(), "Game 3: 4 blue, 1 red; 0 green");
+}
+
+/// A hand that shows more of a color than the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    game: u32,
+    /// 1-based, in the order the game lists its hands.
+    hand: usize,
+    color: String,
+    count: u32,
+    limit: u32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Game {}, hand {}: {} {} but the bag holds {}",
+            self.game, self.hand, self.count, self.color, self.limit
+        )
+    }
+}
 
-    let part1 = input
+/// Every way `game` couldn't have been played with `bag`. A color the bag doesn't list
+/// can't be drawn at all.
+fn violations(game: &Game, bag: &Hand) -> Vec<Violation> {
+    game.hands
         .iter()
-        .filter_map(|line| parse_game(line))
-        .filter(|game| {
-            game.hands
+        .enumerate()
+        .flat_map(|(i, hand)| {
+            hand.counts
                 .iter()
-                .all(|hand| hand.red <= 12 && hand.green <= 13 && hand.blue <= 14)
+                .filter(|(color, count)| **count > bag.count(color))
+                .map(move |(color, count)| Violation {
+                    game: game.id,
+                    hand: i + 1,
+                    color: color.clone(),
+                    count: *count,
+                    limit: bag.count(color),
+                })
         })
-        .map(|game| game.id)
-        .sum::<u32>();
+        .collect()
+}
 
-    println!("Part 1: {}", part1);
+/// Strict parsing also wants ids to count up from 1. Errors give the line in the input,
+/// counting blank lines.
+fn parse_games(input: &Input, mode: Mode) -> Result<Vec<Game>, ParseError> {
+    if mode == Mode::Lenient {
+        return Ok(input
+            .lines()
+            .filter_map(|line| parse_game(line).ok())
+            .collect());
+    }
+
+    let mut games = vec![];
+    let mut seen = HashSet::new();
+    for (i, (n, line)) in input.numbered_lines().enumerate() {
+        let (game, id_at) = parse_game_strict(line).map_err(|e| e.on_line(n))?;
+
+        let expected = i as u32 + 1;
+        let message = if !seen.insert(game.id) {
+            format!("duplicate game id {}", game.id)
+        } else if game.id != expected {
+            format!("game id {} out of sequence, expected {}", game.id, expected)
+        } else {
+            games.push(game);
+            continue;
+        };
+        return Err(ParseError::new(id_at, message).on_line(n));
+    }
+
+    Ok(games)
+}
+
+#[test]
+fn test_parse_games() {
+    let input = Input::from_contents("Game 1: 1 red\nGame 2: 2 red\nGame 2: 3 red\n");
+    assert_eq!(parse_games(&input, Mode::Lenient).unwrap().len(), 3);
+    assert_eq!(
+        parse_games(&input, Mode::Strict).unwrap_err().to_string(),
+        "line 3, column 6: duplicate game id 2"
+    );
+
+    let input = Input::from_contents("Game 1: 1 red\nGame 3: 2 red\n");
+    assert_eq!(
+        parse_games(&input, Mode::Strict).unwrap_err().to_string(),
+        "line 2, column 6: game id 3 out of sequence, expected 2"
+    );
+
+    let input = Input::from_contents("Game 1: 1 red\nGame 2: 2 red, 1 red\n");
+    assert_eq!(
+        parse_games(&input, Mode::Strict).unwrap_err().to_string(),
+        "line 2, column 18: red given twice in one hand"
+    );
+
+    let input = Input::from_contents("Game 1: 1 red\n\nGame 2: 2 red\nGame 2: 3 red\n");
+    assert_eq!(
+        parse_games(&input, Mode::Strict).unwrap_err().to_string(),
+        "line 4, column 6: duplicate game id 2"
+    );
+
+    let input = common::generate::generate(2, 7, 200).unwrap();
+    assert_eq!(
+        parse_games(&Input::from_contents(&input), Mode::Strict).unwrap(),
+        parse_games(&Input::from_contents(&input), Mode::Lenient).unwrap()
+    );
+}
+
+fn load_games(mode: Mode) -> Result<Vec<Game>, Box<dyn Error>> {
+    Ok(parse_games(&Input::load("day02")?, mode)?)
+}
 
-    let part2 = input
+/// The fewest cubes of each color that could have shown all of `hands`.
+fn minimum_bag<'a>(hands: impl IntoIterator<Item = &'a Hand>) -> Hand {
+    let mut counts = BTreeMap::new();
+    for hand in hands {
+        for (color, count) in &hand.counts {
+            let most = counts.entry(color.clone()).or_insert(0);
+            *most = (*most).max(*count);
+        }
+    }
+
+    Hand { counts }
+}
+
+/// Every color shown anywhere in `games`.
+fn colors(games: &[Game]) -> BTreeSet<&str> {
+    games
+        .iter()
+        .flat_map(|game| &game.hands)
+        .flat_map(|hand| hand.counts.keys())
+        .map(|color| color.as_str())
+        .collect()
+}
+
+/// Part 1 sums the games possible with `bag`, the violations say why the rest aren't.
+/// Part 2's power multiplies the fewest cubes of every color seen anywhere in the input,
+/// so a game that never shows one of them has no power.
+fn solve(
+    input: &Input,
+    bag: &Hand,
+    mode: Mode,
+) -> Result<(Answer, Answer, Vec<Violation>), Box<dyn Error>> {
+    let games = parse_games(input, mode)?;
+
+    let mut part1 = Answer::ZERO;
+    let mut impossible = vec![];
+    for game in &games {
+        let found = violations(game, bag);
+        if found.is_empty() {
+            part1 = part1.checked_add(game.id)?;
+        }
+        impossible.extend(found);
+    }
+
+    let colors = colors(&games);
+    let powers = games
         .iter()
-        .filter_map(|line| parse_game(line))
         .map(|game| {
-            // Find the max of each red, green, blue
-            let max_red = game.hands.iter().map(|hand| hand.red).max().unwrap_or(0);
-            let max_green = game.hands.iter().map(|hand| hand.green).max().unwrap_or(0);
-            let max_blue = game.hands.iter().map(|hand| hand.blue).max().unwrap_or(0);
+            let fewest = minimum_bag(&game.hands);
+            answer::product(colors.iter().map(|color| fewest.count(color)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let part2 = answer::sum(powers)?;
+
+    Ok((part1, part2, impossible))
+}
+
+#[test]
+fn test_solve() {
+    let input = Input::from_contents(
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+         Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+         Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    );
+    let bag = parse_bag(BAG).unwrap();
+    let (part1, part2, impossible) = solve(&input, &bag, Mode::Strict).unwrap();
+    assert_eq!((part1, part2), (Answer(8), Answer(2286)));
+    assert_eq!(
+        impossible.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+        vec![
+            "Game 3, hand 1: 20 red but the bag holds 12",
+            "Game 4, hand 3: 15 blue but the bag holds 14",
+            "Game 4, hand 3: 14 red but the bag holds 12",
+        ]
+    );
+
+    let (part1, _, impossible) = solve(
+        &input,
+        &parse_bag("20 red, 13 green, 15 blue").unwrap(),
+        Mode::Lenient,
+    )
+    .unwrap();
+    assert_eq!(part1, Answer(15));
+    assert!(impossible.is_empty());
+
+    // A color the bag doesn't have rules a game out, and counts towards every power.
+    let input = Input::from_contents(
+        "Game 1: 1 red, 1 green, 1 blue, 2 gold
+         Game 2: 2 red, 2 green, 2 blue",
+    );
+    let (part1, part2, impossible) = solve(&input, &bag, Mode::Strict).unwrap();
+    assert_eq!((part1, part2), (Answer(2), Answer(2)));
+    assert_eq!(
+        impossible[0].to_string(),
+        "Game 1, hand 1: 2 gold but the bag holds 0"
+    );
+}
+
+/// How many of `games` each bag makes possible, most first. Ties keep the order the bags
+/// were given in.
+fn rank_bags<'a>(games: &[Game], bags: &'a [Hand]) -> Vec<(&'a Hand, usize)> {
+    let mut ranked = bags
+        .iter()
+        .map(|bag| {
+            let possible = games
+                .iter()
+                .filter(|game| violations(game, bag).is_empty())
+                .count();
+            (bag, possible)
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by_key(|(_, possible)| std::cmp::Reverse(*possible));
+    ranked
+}
+
+/// The smallest bag that makes every game in `ids` possible.
+fn minimal_bag(games: &[Game], ids: &[u32]) -> Result<Hand, Box<dyn Error>> {
+    let chosen = ids
+        .iter()
+        .map(|id| {
+            games
+                .iter()
+                .find(|game| game.id == *id)
+                .ok_or_else(|| format!("No game {}", id))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(minimum_bag(chosen.iter().flat_map(|game| &game.hands)))
+}
+
+/// For each color, how many games need each number of its cubes at least. A game that
+/// never shows a color needs none of it.
+fn maxima(games: &[Game]) -> BTreeMap<&str, BTreeMap<u32, usize>> {
+    let fewest = games
+        .iter()
+        .map(|game| minimum_bag(&game.hands))
+        .collect::<Vec<_>>();
+
+    colors(games)
+        .into_iter()
+        .map(|color| {
+            let mut distribution = BTreeMap::new();
+            for bag in &fewest {
+                *distribution.entry(bag.count(color)).or_insert(0) += 1;
+            }
+            (color, distribution)
+        })
+        .collect()
+}
+
+#[test]
+fn test_queries() {
+    let games = parse_games(
+        &Input::from_contents(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+         Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+         Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red",
+        ),
+        Mode::Strict,
+    )
+    .unwrap();
+
+    let bags = ["4 red, 3 green, 6 blue", BAG, "20 red, 13 green, 15 blue"]
+        .map(|bag| parse_bag(bag).unwrap());
+    let ranked = rank_bags(&games, &bags)
+        .into_iter()
+        .map(|(bag, possible)| (bag.to_string(), possible))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranked,
+        vec![
+            ("15 blue, 13 green, 20 red".to_string(), 5),
+            ("14 blue, 13 green, 12 red".to_string(), 3),
+            ("6 blue, 3 green, 4 red".to_string(), 2),
+        ]
+    );
+
+    assert_eq!(
+        minimal_bag(&games, &[1, 5]).unwrap(),
+        Hand::from_iter([("red", 6), ("green", 3), ("blue", 6)])
+    );
+    assert_eq!(minimal_bag(&games, &[]).unwrap(), Hand::from_iter([]));
+    assert_eq!(
+        minimal_bag(&games, &[6]).unwrap_err().to_string(),
+        "No game 6"
+    );
+
+    let maxima = maxima(&games);
+    assert_eq!(
+        maxima["red"],
+        BTreeMap::from([(1, 1), (4, 1), (6, 1), (14, 1), (20, 1)])
+    );
+    assert_eq!(maxima["green"], BTreeMap::from([(2, 1), (3, 3), (13, 1)]));
+}
 
-            max_red * max_green * max_blue
+#[test]
+fn test_crlf_input() {
+    let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+"
+    .replace('\n', "\r\n");
+
+    let (part1, part2, _) = solve(
+        &Input::from_contents(&input),
+        &parse_bag(BAG).unwrap(),
+        Mode::Strict,
+    )
+    .unwrap();
+    assert_eq!((part1, part2), (Answer(8), Answer(2286)));
+}
+
+#[test]
+fn test_malformed_input() {
+    for mode in [Mode::Lenient, Mode::Strict] {
+        common::robustness::check(2, 50, 200, move |input| {
+            solve(&Input::from_contents(input), &parse_bag(BAG).unwrap(), mode)
         })
-        .sum::<u32>();
+        .unwrap();
+    }
+}
+
+fn rank(args: &[String], mode: Mode) -> Result<(), Box<dyn Error>> {
+    let mut bags = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let bag = match arg.as_str() {
+            "--bag-file" => {
+                let path = args.next().ok_or("Missing value for --bag-file")?;
+                std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read bag from {}: {}", path, e))?
+            }
+            _ => arg.clone(),
+        };
+        bags.push(parse_bag(&bag)?);
+    }
+    if bags.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    let games = load_games(mode)?;
+    for (bag, possible) in rank_bags(&games, &bags) {
+        println!(
+            "{} of {} games possible with {}",
+            possible,
+            games.len(),
+            bag
+        );
+    }
+
+    Ok(())
+}
+
+fn minimal(args: &[String], mode: Mode) -> Result<(), Box<dyn Error>> {
+    let ids = args
+        .iter()
+        .map(|id| id.parse())
+        .collect::<Result<Vec<u32>, _>>()?;
+    if ids.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    let games = load_games(mode)?;
+    println!("{}", minimal_bag(&games, &ids)?);
+
+    Ok(())
+}
+
+fn print_maxima(mode: Mode) -> Result<(), Box<dyn Error>> {
+    let games = load_games(mode)?;
+
+    for (color, distribution) in maxima(&games) {
+        println!("{}", color);
+        for (most, count) in distribution {
+            println!("  {:>4}: {} games", most, count);
+        }
+    }
+
+    Ok(())
+}
+
+fn answers(args: &[String], mode: Mode) -> Result<(), Box<dyn Error>> {
+    let mut bag = BAG.to_string();
+    let mut report = false;
+
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--bag" => bag = flags.next().ok_or("Missing value for --bag")?.clone(),
+            "--bag-file" => {
+                let path = flags.next().ok_or("Missing value for --bag-file")?;
+                bag = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read bag from {}: {}", path, e))?;
+            }
+            "--report" => report = true,
+            _ => return Err(format!("Unknown flag: {}\n{}", flag, USAGE).into()),
+        }
+    }
+    let bag = parse_bag(&bag)?;
 
+    let input = Input::load("day02")?;
+
+    let (part1, part2, impossible) = solve(&input, &bag, mode)?;
+
+    if report {
+        for violation in &impossible {
+            println!("{}", violation);
+        }
+    }
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
 }
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mode = if args.iter().any(|arg| arg == "--strict") {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    };
+    args.retain(|arg| arg != "--strict");
+
+    match args.first().map(|s| s.as_str()) {
+        Some("rank") => rank(&args[1..], mode),
+        Some("minimal") => minimal(&args[1..], mode),
+        Some("maxima") => print_maxima(mode),
+        _ => answers(&args, mode),
+    }
+}
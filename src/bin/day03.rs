@@ -1,4 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::HashMap, error::Error};
+
+use common::{
+    answer::{self, Answer},
+    Input,
+};
 
 #[derive(Debug)]
 struct Schematic {
@@ -21,50 +26,72 @@ fn test_is_symbol() {
     assert!(is_symbol('h'));
 }
 
-impl Schematic {
-    /// Returns the coordinates of any predicate fn.
-    fn is_adjacent_to_symbol<F>(&self, row: usize, col: usize, f: F) -> Vec<(usize, usize)>
-    where
-        F: Fn(char) -> bool,
-    {
-        let offsets = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        let x = row as i32;
-        let y = col as i32;
-
-        let mut out = vec![];
-
-        for (row_offset, col_offset) in offsets.iter() {
-            let adj_row = x + row_offset;
-            let adj_col = y + col_offset;
-
-            let Ok(adj_row) = usize::try_from(adj_row) else {
-                continue;
-            };
+/// A run of digits on one row of the schematic, `col_end` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PartNumber {
+    value: u32,
+    row: usize,
+    col_start: usize,
+    col_end: usize,
+}
 
-            let Ok(adj_col) = usize::try_from(adj_col) else {
-                continue;
-            };
+impl Schematic {
+    /// Every number in the schematic, row by row.
+    fn numbers(&self) -> Result<Vec<PartNumber>, Box<dyn Error>> {
+        let mut numbers = vec![];
+
+        for (row, line) in self.data.iter().enumerate() {
+            let mut col = 0;
+            while col < line.len() {
+                let col_start = col;
+                let mut value: u32 = 0;
+
+                while let Some(digit) = line.get(col).and_then(|c| c.to_digit(10)) {
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(digit))
+                        .ok_or("Part number too large")?;
+                    col += 1;
+                }
+
+                if col > col_start {
+                    numbers.push(PartNumber {
+                        value,
+                        row,
+                        col_start,
+                        col_end: col,
+                    });
+                }
+                col += 1;
+            }
+        }
 
-            let Some(line) = self.data.get(adj_row) else {
-                continue;
-            };
+        Ok(numbers)
+    }
 
-            let Some(&c) = line.get(adj_col) else {
-                continue;
-            };
+    /// The symbols next to `number` with their coordinates, from one pass over the box
+    /// around it. The number's own cells are digits so are never picked up.
+    fn adjacent_symbols(&self, number: &PartNumber) -> Vec<(usize, usize, char)> {
+        let rows = number.row.saturating_sub(1)..=number.row + 1;
+        let cols = number.col_start.saturating_sub(1)..=number.col_end;
 
-            if f(c) {
-                out.push((adj_row, adj_col));
+        let mut out = vec![];
+        for (row, line) in self
+            .data
+            .iter()
+            .enumerate()
+            .take(*rows.end() + 1)
+            .skip(*rows.start())
+        {
+            for (col, &c) in line
+                .iter()
+                .enumerate()
+                .take(*cols.end() + 1)
+                .skip(*cols.start())
+            {
+                if is_symbol(c) {
+                    out.push((row, col, c));
+                }
             }
         }
 
@@ -72,73 +99,119 @@ impl Schematic {
     }
 }
 
-type GearRatios = HashMap<(usize, usize), Vec<u32>>;
-
-fn walk(schematic: &Schematic) -> (u32, GearRatios) {
-    let mut sum = 0;
-
-    let mut gear_ratios: GearRatios = HashMap::new();
+#[test]
+fn test_part_numbers() {
+    let input = common::lines("467..114..\n...*......\n..35..633.\n......#...\n");
+    let schematic = Schematic {
+        data: input.iter().map(|line| line.chars().collect()).collect(),
+    };
 
-    for (i, line) in schematic.data.iter().enumerate() {
-        let mut col = 0;
-        while col < line.len() {
-            let mut num = 0;
-            let mut next_to_symbol = false;
+    let numbers = schematic.numbers().unwrap();
+    assert_eq!(
+        numbers[0],
+        PartNumber {
+            value: 467,
+            row: 0,
+            col_start: 0,
+            col_end: 3,
+        }
+    );
+    assert_eq!(
+        numbers.iter().map(|n| n.value).collect::<Vec<_>>(),
+        vec![467, 114, 35, 633]
+    );
 
-            let mut gear_coordinates_for_num = HashSet::new();
+    let symbols = numbers
+        .iter()
+        .map(|number| schematic.adjacent_symbols(number))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        symbols,
+        vec![
+            vec![(1, 3, '*')],
+            vec![],
+            vec![(1, 3, '*')],
+            vec![(3, 6, '#')]
+        ]
+    );
+}
 
-            while let Some(digit) = line.get(col).and_then(|c| c.to_digit(10)) {
-                // Find all the adjacent gears for this digit.
-                let gear_coordinates = schematic.is_adjacent_to_symbol(i, col, is_gear);
-                gear_coordinates_for_num.extend(gear_coordinates);
+type GearRatios = HashMap<(usize, usize), Vec<u32>>;
 
-                next_to_symbol = next_to_symbol
-                    || !schematic
-                        .is_adjacent_to_symbol(i, col, is_symbol)
-                        .is_empty();
+fn walk(schematic: &Schematic) -> Result<(Answer, GearRatios), Box<dyn Error>> {
+    let mut sum = Answer::ZERO;
 
-                num *= 10;
-                num += digit;
-                col += 1;
-            }
+    let mut gear_ratios: GearRatios = HashMap::new();
 
-            if next_to_symbol {
-                sum += num;
-            }
+    for number in schematic.numbers()? {
+        let symbols = schematic.adjacent_symbols(&number);
 
-            // For each gear near this number insert it into the gear ratios hashmap.
-            for (row, col) in gear_coordinates_for_num {
-                let gear_ratios_for_gear = gear_ratios.entry((row, col)).or_default();
-                gear_ratios_for_gear.push(num);
-            }
+        if !symbols.is_empty() {
+            sum = sum.checked_add(number.value)?;
+        }
 
-            col += 1;
+        // Each cell of the box is only looked at once, so a gear can't list a number twice.
+        for (row, col, _) in symbols.into_iter().filter(|(_, _, c)| is_gear(*c)) {
+            gear_ratios
+                .entry((row, col))
+                .or_default()
+                .push(number.value);
         }
     }
-    (sum, gear_ratios)
+    Ok((sum, gear_ratios))
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = common::read_data("day03")?;
-
+fn solve(input: &Input) -> Result<(Answer, Answer), Box<dyn Error>> {
     let schematic = Schematic {
-        data: input.iter().map(|line| line.chars().collect()).collect(),
+        data: input.lines().map(|line| line.chars().collect()).collect(),
     };
 
-    let (part1, gear_ratios) = walk(&schematic);
-    println!("Part 1: {}", part1);
+    let (part1, gear_ratios) = walk(&schematic)?;
 
     // For each gear, if there's only two numbers then the ratio is the multiplication of those two numbers.
-    let part2 = gear_ratios
-        .iter()
-        .filter_map(|(_gear, numbers)| {
-            if numbers.len() == 2 {
-                Some(numbers[0] * numbers[1])
-            } else {
-                None
-            }
-        })
-        .sum::<u32>();
+    let part2 = answer::sum(
+        gear_ratios
+            .values()
+            .filter(|numbers| numbers.len() == 2)
+            .map(|numbers| numbers[0] as u64 * numbers[1] as u64),
+    )?;
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+"
+    .replace('\n', "\r\n");
+
+    assert_eq!(
+        solve(&Input::from_contents(&input)).unwrap(),
+        (Answer(4361), Answer(467835))
+    );
+}
+
+#[test]
+fn test_malformed_input() {
+    assert!(solve(&Input::from_contents("4294967296*")).is_err());
+
+    common::robustness::check(3, 30, 200, |input| solve(&Input::from_contents(input))).unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = Input::load("day03")?;
+
+    let (part1, part2) = solve(&input)?;
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
@@ -1,5 +1,12 @@
-use std::error::Error;
+use std::{error::Error, fmt};
 
+use common::{
+    answer::{self, Answer, Overflow},
+    parse::{self, ParseError},
+    Input,
+};
+
+#[derive(Debug, PartialEq, Eq)]
 struct Card {
     id: u32,
     winning_numbers: Vec<u32>,
@@ -7,36 +14,19 @@ struct Card {
 }
 
 impl Card {
-    fn from_str(s: &str) -> Option<Card> {
-        // Split the input string into two parts: the ID and the numbers part.
-        let mut parts = s.split(": ");
-        let id_part = parts.next()?;
-        let numbers_part = parts.next()?;
-
-        // Extract the ID from the ID part.
-        let id = id_part.split_whitespace().nth(1)?.parse::<u32>().ok()?;
+    fn from_str(s: &str) -> Result<Card, ParseError> {
+        let (_, id, numbers) = parse::labelled::<u32>(s)?;
 
         // Split the numbers part into winning and scratch numbers.
-        let mut number_parts = numbers_part.split(" | ");
-        let winning_numbers_str = number_parts.next()?;
-        let scratch_numbers_str = number_parts.next()?;
-
-        // Parse winning numbers.
-        let winning_numbers = winning_numbers_str
-            .split_whitespace()
-            .filter_map(|n| n.parse::<u32>().ok())
-            .collect::<Vec<u32>>();
-
-        // Parse scratch numbers.
-        let scratch_numbers = scratch_numbers_str
-            .split_whitespace()
-            .filter_map(|n| n.parse::<u32>().ok())
-            .collect::<Vec<u32>>();
-
-        Some(Card {
+        let (winning_numbers, scratch_numbers) = numbers
+            .split_once('|')
+            .ok_or_else(|| ParseError::new(s.trim_end().len(), "expected \"|\""))?;
+        let ints = |numbers| parse::ints(numbers).map_err(|e| e.within(s, numbers));
+
+        Ok(Card {
             id,
-            winning_numbers,
-            scratch_numbers,
+            winning_numbers: ints(winning_numbers)?,
+            scratch_numbers: ints(scratch_numbers)?,
         })
     }
 
@@ -53,13 +43,34 @@ impl Card {
         winning_set.intersection(&scratch_set).count()
     }
 
-    fn score(&self) -> u32 {
+    fn score(&self) -> Result<Answer, Overflow> {
         let matches = self.matches();
         if matches == 0 {
-            return 0;
+            return Ok(Answer::ZERO);
         }
 
-        2u32.pow(matches as u32 - 1)
+        let exp = u32::try_from(matches - 1).map_err(|_| Overflow)?;
+        Answer(2).checked_pow(exp)
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |numbers: &[u32]| {
+            numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        write!(
+            f,
+            "Card {}: {} | {}",
+            self.id,
+            join(&self.winning_numbers),
+            join(&self.scratch_numbers)
+        )
     }
 }
 
@@ -71,6 +82,23 @@ fn test_card_from_str() {
     assert_eq!(card.scratch_numbers, vec![4, 5, 6]);
 }
 
+#[test]
+fn test_round_trip() {
+    for seed in 0..20 {
+        for line in common::lines(&common::generate::generate(4, seed, 50).unwrap()) {
+            let card = Card::from_str(&line).unwrap();
+            let printed = card.to_string();
+
+            assert_eq!(Card::from_str(&printed), Ok(card));
+            assert_eq!(Card::from_str(&printed).unwrap().to_string(), printed);
+        }
+    }
+
+    // The column padding in real inputs is dropped.
+    let card = Card::from_str("Card   7:  1 21 | 83  6").unwrap();
+    assert_eq!(card.to_string(), "Card 7: 1 21 | 83 6");
+}
+
 #[test]
 fn test_score() {
     let card = Card {
@@ -78,7 +106,7 @@ fn test_score() {
         winning_numbers: vec![1, 2, 3],
         scratch_numbers: vec![1, 2, 3],
     };
-    assert_eq!(card.score(), 4);
+    assert_eq!(card.score(), Ok(Answer(4)));
 
     // Zero matches.
     let card = Card {
@@ -86,53 +114,100 @@ fn test_score() {
         winning_numbers: vec![1, 2, 3],
         scratch_numbers: vec![4, 5, 6],
     };
-    assert_eq!(card.score(), 0);
+    assert_eq!(card.score(), Ok(Answer::ZERO));
+
+    // More matches than a u32 score can hold.
+    let card = Card {
+        id: 1,
+        winning_numbers: (0..40).collect(),
+        scratch_numbers: (0..40).collect(),
+    };
+    assert_eq!(card.score(), Ok(Answer(1 << 39)));
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = common::read_data("day04")?;
+fn solve(input: &Input) -> Result<(Answer, Answer), Box<dyn Error>> {
+    let card_vec = input
+        .numbered_lines()
+        .map(|(n, line)| Card::from_str(line).map_err(|e| e.on_line(n)))
+        .collect::<Result<Vec<Card>, _>>()?;
 
-    let part1 = input
+    let scores = card_vec
         .iter()
-        .filter_map(|l| Card::from_str(l))
         .map(|c| c.score())
-        .sum::<u32>();
-
-    println!("Part 1: {}", part1);
+        .collect::<Result<Vec<_>, _>>()?;
+    let part1 = answer::sum(scores)?;
 
     // Now part 2.
-    let card_vec = input
-        .iter()
-        .filter_map(|l| Card::from_str(l))
-        .collect::<Vec<Card>>();
 
     // We start with 1 copy of each card.
     let mut id_to_copies = card_vec
         .iter()
-        .map(|c| (c.id, 1))
-        .collect::<std::collections::HashMap<u32, u32>>();
+        .map(|c| (c.id, Answer::ONE))
+        .collect::<std::collections::HashMap<u32, Answer>>();
 
     for card in card_vec {
         let matches = card.matches();
 
-        let Some(copies) = id_to_copies.get(&card.id) else {
+        let Some(&copies) = id_to_copies.get(&card.id) else {
             break;
         };
 
-        for _ in 0..*copies {
-            for i in 0..matches {
-                let id = card.id + i as u32 + 1;
-                // Add a copy of the card to the deck.
-                let Some(copies) = id_to_copies.get(&id) else {
-                    break;
-                };
-                id_to_copies.insert(id, copies + 1);
-            }
+        for i in 0..matches {
+            let Some(id) = card.id.checked_add(i as u32 + 1) else {
+                break;
+            };
+            // Add a copy of the card to the deck for every copy of this card.
+            let Some(won) = id_to_copies.get_mut(&id) else {
+                break;
+            };
+            *won = won.checked_add(copies)?;
         }
     }
 
-    let part2 = id_to_copies.values().sum::<u32>();
+    let part2 = answer::sum(id_to_copies.into_values())?;
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+"
+    .replace('\n', "\r\n");
+
+    assert_eq!(
+        solve(&Input::from_contents(&input)).unwrap(),
+        (Answer(13), Answer(30))
+    );
+}
+
+#[test]
+fn test_malformed_input() {
+    let input = Input::from_contents("Card 1: 1 2 | 3\n\nCard 2: 1 x | 3\nCard 3: 1 2 3\n");
+    assert_eq!(
+        solve(&input).unwrap_err().to_string(),
+        "line 3, column 11: expected an integer"
+    );
+    let input = Input::from_contents("Card 1: 1 2 | 3\nCard 2: 1 2 3\n");
+    assert_eq!(
+        solve(&input).unwrap_err().to_string(),
+        "line 2, column 14: expected \"|\""
+    );
+
+    common::robustness::check(4, 50, 200, |input| solve(&Input::from_contents(input))).unwrap();
+}
 
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = Input::load("day04")?;
+
+    let (part1, part2) = solve(&input)?;
+
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
@@ -1,28 +1,34 @@
+use common::{
+    parse::{self, ParseError},
+    ranges::{Interval, RangeMap, RangeSet},
+    Input,
+};
 use itertools::{self, Itertools};
 use std::error::Error;
 
-fn parse_paragraph(paragraph: &str) -> Vec<(u64, u64, u64)> {
-    let mut lines = paragraph.trim().lines();
+/// A map section whose header is on line `line` of the almanac.
+fn parse_paragraph(paragraph: &str, line: usize) -> Result<Vec<(u64, u64, u64)>, ParseError> {
+    let (_, rows) = parse::section(paragraph).map_err(|e| e.on_line(line))?;
 
-    // Skip the first line.
-    lines.next();
-
-    lines
-        .filter_map(|line| {
-            let mut parts = line.split_whitespace();
-            let seed = parts.next()?.parse::<u64>().ok()?;
-            let to_soil = parts.next()?.parse::<u64>().ok()?;
-            let soil = parts.next()?.parse::<u64>().ok()?;
-            Some((seed, to_soil, soil))
-        })
+    rows.lines()
+        .zip(line + 1..)
+        .filter(|(row, _)| !row.trim().is_empty())
+        .map(
+            |(row, n)| match parse::ints::<u64>(row).map_err(|e| e.on_line(n))?[..] {
+                [dest, source, length] => Ok((dest, source, length)),
+                ref numbers => Err(ParseError::new(
+                    row.len() - row.trim_start().len(),
+                    format!("expected 3 numbers in a map row, found {}", numbers.len()),
+                )
+                .on_line(n)),
+            },
+        )
         .collect()
 }
 
-fn parse_seeds(seeds: &str) -> Vec<u64> {
-    seeds
-        .split_whitespace()
-        .filter_map(|s| s.parse::<u64>().ok())
-        .collect()
+fn parse_seeds(seeds: &str) -> Result<Vec<u64>, ParseError> {
+    let (_, numbers) = parse::key_value(seeds)?;
+    parse::ints(numbers).map_err(|e| e.within(seeds, numbers))
 }
 
 #[test]
@@ -30,121 +36,236 @@ fn test_parse_seeds() {
     let seeds = "seeds: 1 2 3 4 5";
 
     let expected = vec![1, 2, 3, 4, 5];
-    let actual = parse_seeds(seeds);
+    let actual = parse_seeds(seeds).unwrap();
     assert_eq!(expected, actual);
 }
 
 #[test]
 fn test_parse_paragraph() {
     let paragraph = r#"
-        foo
+        foo:
         1 2 3
         2 3 4
         3 4 5
     "#;
 
     let expected = vec![(1, 2, 3), (2, 3, 4), (3, 4, 5)];
-    let actual = parse_paragraph(paragraph);
+    let actual = parse_paragraph(paragraph, 1).unwrap();
     assert_eq!(expected, actual);
+
+    assert_eq!(
+        parse_paragraph("foo:\n1 2 3\n  2 3\n", 4)
+            .unwrap_err()
+            .to_string(),
+        "line 6, column 3: expected 3 numbers in a map row, found 2"
+    );
+    assert_eq!(
+        parse_paragraph("foo:\n1 2 x\n", 4).unwrap_err().to_string(),
+        "line 5, column 5: expected an integer"
+    );
 }
 
-fn mapping(num: u64, to: &[(u64, u64, u64)]) -> u64 {
-    // to is dest range start, source range start, range length
-    for (dest, source, length) in to {
-        if num >= *source && num < source + length {
-            return dest + (num - source);
-        }
+fn range_map(rows: &[(u64, u64, u64)]) -> Option<RangeMap> {
+    let mut map = RangeMap::new();
+
+    // A row is dest range start, source range start, range length.
+    for &(dest, source, length) in rows {
+        map.insert(Interval::from_len(source, length)?, dest)?;
     }
 
-    num
+    Some(map)
 }
 
 #[test]
 fn test_mapping() {
-    let to = vec![(50, 98, 2), (52, 50, 48)];
+    let to = range_map(&[(50, 98, 2), (52, 50, 48)]).unwrap();
+
+    assert_eq!(to.get(98), 50);
+    assert_eq!(to.get(99), 51);
+    assert_eq!(to.get(10), 10);
 
-    assert_eq!(mapping(98, &to), 50);
-    assert_eq!(mapping(99, &to), 51);
+    assert!(range_map(&[(50, 98, 2), (0, 99, 1)]).is_none());
 }
 
-fn part2(input: &str) -> Option<()> {
-    // Split by paragraph.
-    let mut paragraphs = input.split("\n\n");
+fn seed_ranges(seeds: &[u64]) -> Option<RangeSet> {
+    seeds
+        .iter()
+        .tuples()
+        .map(|(&seed, &range)| Interval::from_len(seed, range))
+        .collect()
+}
 
-    let seeds = parse_seeds(paragraphs.next()?);
-    let seed_to_soil = parse_paragraph(paragraphs.next()?);
-    let soil_to_fertilizer = parse_paragraph(paragraphs.next()?);
-    let fertilizer_to_water = parse_paragraph(paragraphs.next()?);
-    let water_to_light = parse_paragraph(paragraphs.next()?);
-    let light_to_temperature = parse_paragraph(paragraphs.next()?);
-    let temperature_to_humidity = parse_paragraph(paragraphs.next()?);
-    let humidity_to_location = parse_paragraph(paragraphs.next()?);
+type Almanac = (Vec<u64>, Vec<RangeMap>);
 
-    let mut lowest = std::u64::MAX;
+fn parse_almanac(input: &Input) -> Result<Almanac, Box<dyn Error>> {
+    let mut paragraphs = input.numbered_sections();
 
-    let mut seeds_by_pairs = vec![];
-    for (seed, range) in seeds.into_iter().tuples() {
-        for i in 0..range {
-            seeds_by_pairs.push(seed + i);
-        }
-    }
+    let (line, seeds) = paragraphs.next().ok_or("No seeds")?;
+    let seeds = parse_seeds(seeds).map_err(|e| e.on_line(line))?;
 
-    for seed in seeds_by_pairs {
-        // Walk everything till we get to location.
-        let mut num = seed;
-        num = mapping(num, &seed_to_soil);
-        num = mapping(num, &soil_to_fertilizer);
-        num = mapping(num, &fertilizer_to_water);
-        num = mapping(num, &water_to_light);
-        num = mapping(num, &light_to_temperature);
-        num = mapping(num, &temperature_to_humidity);
-        num = mapping(num, &humidity_to_location);
-
-        lowest = std::cmp::min(lowest, num);
+    let mut maps = vec![];
+    for (line, paragraph) in paragraphs {
+        let map = range_map(&parse_paragraph(paragraph, line)?)
+            .ok_or_else(|| format!("line {}: map ranges overlap or overflow", line))?;
+        maps.push(map);
+    }
+    if maps.len() != 7 {
+        return Err(format!("Expected 7 maps, found {}", maps.len()).into());
     }
 
-    println!("Part 2: {}", lowest);
-
-    Some(())
-}
-
-fn part1(input: &str) -> Option<()> {
-    // Split by paragraph.
-    let mut paragraphs = input.split("\n\n");
-
-    let seeds = parse_seeds(paragraphs.next()?);
-    let seed_to_soil = parse_paragraph(paragraphs.next()?);
-    let soil_to_fertilizer = parse_paragraph(paragraphs.next()?);
-    let fertilizer_to_water = parse_paragraph(paragraphs.next()?);
-    let water_to_light = parse_paragraph(paragraphs.next()?);
-    let light_to_temperature = parse_paragraph(paragraphs.next()?);
-    let temperature_to_humidity = parse_paragraph(paragraphs.next()?);
-    let humidity_to_location = parse_paragraph(paragraphs.next()?);
-
-    let mut lowest = std::u64::MAX;
-    for seed in seeds {
-        // Walk everything till we get to location.
-        let mut num = seed;
-        num = mapping(num, &seed_to_soil);
-        num = mapping(num, &soil_to_fertilizer);
-        num = mapping(num, &fertilizer_to_water);
-        num = mapping(num, &water_to_light);
-        num = mapping(num, &light_to_temperature);
-        num = mapping(num, &temperature_to_humidity);
-        num = mapping(num, &humidity_to_location);
-
-        lowest = std::cmp::min(lowest, num);
+    // Ranges running past u64::MAX can't be mapped, reject them up front.
+    seed_ranges(&seeds).ok_or("Seed ranges overflow")?;
+
+    Ok((seeds, maps))
+}
+
+fn location(seed: u64, maps: &[RangeMap]) -> u64 {
+    // Walk everything till we get to location.
+    maps.iter().fold(seed, |num, map| map.get(num))
+}
+
+#[test]
+fn test_mapping_ranges() {
+    let to = range_map(&[(50, 98, 2), (52, 50, 48)]).unwrap();
+
+    let mapped = to.apply(&seed_ranges(&[79, 14, 96, 6]).unwrap());
+    assert_eq!(
+        mapped.intervals(),
+        [
+            Interval::new(50, 52),
+            Interval::new(81, 95),
+            Interval::new(98, 102)
+        ]
+    );
+}
+
+/// Part 2 by trying every seed, only feasible on small inputs.
+#[cfg(test)]
+fn part2_brute_force((seeds, maps): &Almanac) -> Option<u64> {
+    seeds
+        .iter()
+        .copied()
+        .tuples()
+        .flat_map(|(seed, range)| seed..seed + range)
+        .map(|seed| location(seed, maps))
+        .min()
+}
+
+fn part2((seeds, maps): &Almanac) -> Option<u64> {
+    let mut ranges = seed_ranges(seeds)?;
+    for map in maps {
+        ranges = map.apply(&ranges);
     }
 
-    println!("Part 1: {}", lowest);
+    ranges.min()
+}
+
+#[test]
+fn test_part2_differential() {
+    common::differential::check(
+        "day05-part2",
+        5,
+        3,
+        30,
+        |input| {
+            parse_almanac(&Input::from_contents(input))
+                .map(|a| part2_brute_force(&a))
+                .ok()
+        },
+        |input| {
+            parse_almanac(&Input::from_contents(input))
+                .map(|a| part2(&a))
+                .ok()
+        },
+    )
+    .unwrap();
+}
+
+fn part1((seeds, maps): &Almanac) -> Option<u64> {
+    seeds.iter().map(|&seed| location(seed, maps)).min()
+}
 
-    Some(())
+fn solve(input: &Input) -> Result<(u64, u64), Box<dyn Error>> {
+    let almanac = parse_almanac(input)?;
+    let part1 = part1(&almanac).ok_or("No seeds")?;
+    let part2 = part2(&almanac).ok_or("No seeds")?;
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+"
+    .replace('\n', "\r\n");
+
+    assert_eq!(solve(&Input::from_contents(&input)).unwrap(), (35, 46));
+}
+
+#[test]
+fn test_malformed_input() {
+    let maps = "\n\nmap:\n1 2 3".repeat(6);
+    let solve = |input: &str| solve(&Input::from_contents(input));
+    assert!(solve(&format!("seeds: 1 2{}\n\nmap:\n1 2 3", maps)).is_ok());
+    assert!(solve(&format!(
+        "seeds: 1 2{}\n\nmap:\n1 18446744073709551615 2",
+        maps
+    ))
+    .is_err());
+    assert_eq!(
+        solve(&format!("seeds: 1 2{}\n\nmap:\n1 2 x", maps))
+            .unwrap_err()
+            .to_string(),
+        "line 22, column 5: expected an integer"
+    );
+    assert_eq!(
+        solve(&format!("seeds: 1 2{}\n\nmap:\n1 2 3\n10 4 2", maps))
+            .unwrap_err()
+            .to_string(),
+        "line 21: map ranges overlap or overflow"
+    );
+
+    common::robustness::check(5, 10, 200, solve).unwrap();
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let input = std::fs::read_to_string("input/day05.txt")?;
+    let input = Input::load("day05")?;
+
+    let (part1, part2) = solve(&input)?;
+    println!("Part 1: {}", part1);
+    println!("Part 2: {}", part2);
 
-    part1(&input);
-    part2(&input);
     Ok(())
 }
@@ -1,4 +1,6 @@
-use std::{error::Error, str::FromStr};
+use std::{error::Error, fmt, str::FromStr};
+
+use common::parse;
 
 #[derive(Debug, PartialEq, Eq)]
 struct Races {
@@ -10,25 +12,37 @@ impl FromStr for Races {
     type Err = Box<dyn Error>;
 
     fn from_str(input: &str) -> Result<Races, Self::Err> {
-        let mut lines = input.trim().lines();
-
-        let time = lines
-            .next()
-            .ok_or("No time line")?
-            .split_whitespace()
-            .skip(1)
-            .filter_map(|s| s.parse().ok())
-            .collect();
-
-        let distance = lines
-            .next()
-            .ok_or("No distance line")?
-            .split_whitespace()
-            .skip(1)
-            .filter_map(|s| s.parse().ok())
-            .collect();
-
-        Ok(Races { time, distance })
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let mut numbers = |name: &str| -> Result<Vec<u64>, Box<dyn Error>> {
+            let (i, line) = lines.next().ok_or(format!("No {} line", name))?;
+            let (_, numbers) = parse::key_value(line).map_err(|e| e.on_line(i + 1))?;
+            let numbers =
+                parse::ints(numbers).map_err(|e| e.within(line, numbers).on_line(i + 1))?;
+            Ok(numbers)
+        };
+
+        Ok(Races {
+            time: numbers("time")?,
+            distance: numbers("distance")?,
+        })
+    }
+}
+
+impl fmt::Display for Races {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|n| format!(" {}", n))
+                .collect::<String>()
+        };
+
+        writeln!(f, "Time:{}", join(&self.time))?;
+        writeln!(f, "Distance:{}", join(&self.distance))
     }
 }
 
@@ -48,13 +62,36 @@ fn test_from_str() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn test_round_trip() {
+    for seed in 0..20 {
+        let races = common::generate::generate(6, seed, 4)
+            .unwrap()
+            .parse::<Races>()
+            .unwrap();
+        let printed = races.to_string();
+
+        assert_eq!(printed.parse::<Races>().unwrap(), races);
+        assert_eq!(printed.parse::<Races>().unwrap().to_string(), printed);
+    }
+
+    let races = "Time:      7  15\nDistance:  9  40"
+        .parse::<Races>()
+        .unwrap();
+    assert_eq!(races.to_string(), "Time: 7 15\nDistance: 9 40\n");
+}
+
+#[cfg(test)]
 fn wins_against(race_time: u64, distance_to_beat: u64, hold_time: u64) -> bool {
     // For each time unit of hold time we increase our velocity by 1 distance unit / 1 time unit
 
-    let available_time = race_time - hold_time;
-    let distance_covered = available_time * hold_time;
+    // Holding for the whole race (or longer) never moves the boat.
+    let Some(available_time) = race_time.checked_sub(hold_time) else {
+        return false;
+    };
+    let distance_covered = available_time as u128 * hold_time as u128;
 
-    distance_covered > distance_to_beat
+    distance_covered > distance_to_beat as u128
 }
 
 #[test]
@@ -66,8 +103,12 @@ fn test_wins_against() {
     assert!(wins_against(7, 9, 4));
     assert!(wins_against(7, 9, 5));
     assert!(!wins_against(7, 9, 6));
+    assert!(!wins_against(7, 9, 8));
+    assert!(wins_against(u64::MAX, 9, 2));
 }
 
+/// Tries every hold time, kept as the reference for `possible_ways_to_win_quadratic`.
+#[cfg(test)]
 fn possible_ways_to_win(race_time: u64, distance_to_beat: u64) -> usize {
     (0..race_time)
         .skip_while(|hold_time| !wins_against(race_time, distance_to_beat, *hold_time))
@@ -75,24 +116,124 @@ fn possible_ways_to_win(race_time: u64, distance_to_beat: u64) -> usize {
         .count()
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = std::fs::read_to_string("input/day06.txt")?;
+/// Solves `hold * (race_time - hold) > distance_to_beat` directly, the winning hold
+/// times are the integers strictly between the two roots of the quadratic.
+fn possible_ways_to_win_quadratic(race_time: u64, distance_to_beat: u64) -> usize {
+    let t = race_time as u128;
+    let d = distance_to_beat as u128;
+    let wins = |hold: u128| hold * (t - hold) > d;
+
+    let Some(discriminant) = (t * t).checked_sub(4 * d) else {
+        return 0;
+    };
+
+    // The integer square root can be off by one from the real root, nudge onto the
+    // first winning hold time.
+    let mut low = (t - discriminant.isqrt()) / 2;
+    while low > 0 && wins(low - 1) {
+        low -= 1;
+    }
+    while low <= t / 2 && !wins(low) {
+        low += 1;
+    }
+
+    if low > t / 2 {
+        return 0;
+    }
+
+    (t - 2 * low + 1) as usize
+}
+
+#[test]
+fn test_possible_ways_to_win_quadratic() {
+    assert_eq!(possible_ways_to_win_quadratic(7, 9), 4);
+    assert_eq!(possible_ways_to_win_quadratic(15, 40), 8);
+    assert_eq!(possible_ways_to_win_quadratic(30, 200), 9);
+    assert_eq!(possible_ways_to_win_quadratic(71530, 940200), 71503);
+    assert_eq!(possible_ways_to_win_quadratic(4, 4), 0);
+    assert_eq!(possible_ways_to_win_quadratic(4, 3), 1);
+}
 
+fn solve(
+    input: &str,
+    ways_to_win: fn(u64, u64) -> usize,
+) -> Result<(usize, usize), Box<dyn Error>> {
     let races = input.parse::<Races>()?;
 
     let part1 = races
         .time
         .iter()
         .zip(races.distance.iter())
-        .map(|(t, d)| possible_ways_to_win(*t, *d))
-        .product::<usize>();
-
-    println!("Part 1: {}", part1);
+        .try_fold(1usize, |product, (t, d)| {
+            product.checked_mul(ways_to_win(*t, *d))
+        })
+        .ok_or("Product of ways to win too large")?;
 
-    let updated_input = input.replace(' ', "").replace(':', " ");
+    let updated_input = input.replace(' ', "");
     let race = updated_input.parse::<Races>()?;
-    let part2 = possible_ways_to_win(race.time[0], race.distance[0]);
+    let time = race.time.first().ok_or("No time for the long race")?;
+    let distance = race
+        .distance
+        .first()
+        .ok_or("No distance for the long race")?;
+    let part2 = ways_to_win(*time, *distance);
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_solve() {
+    let input = "Time:      7  15   30\nDistance:  9  40  200\n";
+    assert_eq!(
+        solve(input, possible_ways_to_win_quadratic).unwrap(),
+        (288, 71503)
+    );
+}
+
+#[test]
+fn test_solve_differential() {
+    common::differential::check(
+        "day06",
+        6,
+        2,
+        200,
+        |input| solve(input, possible_ways_to_win).ok(),
+        |input| solve(input, possible_ways_to_win_quadratic).ok(),
+    )
+    .unwrap();
+}
 
+#[test]
+fn test_crlf_input() {
+    let input = common::Input::from_contents("Time:      7  15   30\r\nDistance:  9  40  200\r\n");
+    assert_eq!(
+        solve(input.as_str(), possible_ways_to_win).unwrap(),
+        (288, 71503)
+    );
+}
+
+#[test]
+fn test_malformed_input() {
+    assert!(solve("Time: 1\nDistance:", possible_ways_to_win_quadratic).is_err());
+    assert_eq!(
+        solve("Time: 1\nDistance: 2 -3", possible_ways_to_win_quadratic)
+            .unwrap_err()
+            .to_string(),
+        "line 2, column 13: -3 is out of range"
+    );
+
+    common::robustness::check(6, 4, 200, |input| {
+        solve(input, possible_ways_to_win_quadratic)
+    })
+    .unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = common::read_input("day06")?;
+
+    let (part1, part2) = solve(&input, possible_ways_to_win_quadratic)?;
+
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
@@ -1,53 +1,28 @@
-use std::{cmp::max, error::Error, str::FromStr};
+use std::error::Error;
 
+use common::Input;
 use itertools::Itertools;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum Card {
-    A,
-    K,
-    Q,
-    J,
-    T,
-    Nine,
-    Eight,
-    Seven,
-    Six,
-    Five,
-    Four,
-    Three,
-    Two,
-}
-
-impl FromStr for Card {
-    type Err = Box<dyn Error>;
-
-    fn from_str(input: &str) -> Result<Card, Self::Err> {
-        match input {
-            "A" => Ok(Card::A),
-            "K" => Ok(Card::K),
-            "Q" => Ok(Card::Q),
-            "J" => Ok(Card::J),
-            "T" => Ok(Card::T),
-            "9" => Ok(Card::Nine),
-            "8" => Ok(Card::Eight),
-            "7" => Ok(Card::Seven),
-            "6" => Ok(Card::Six),
-            "5" => Ok(Card::Five),
-            "4" => Ok(Card::Four),
-            "3" => Ok(Card::Three),
-            "2" => Ok(Card::Two),
-            _ => Err(format!("Invalid card: {}", input).into()),
-        }
+common::char_enum! {
+    enum Card {
+        A = 'A',
+        K = 'K',
+        Q = 'Q',
+        J = 'J',
+        T = 'T',
+        Nine = '9',
+        Eight = '8',
+        Seven = '7',
+        Six = '6',
+        Five = '5',
+        Four = '4',
+        Three = '3',
+        Two = '2',
     }
 }
 
 fn parse_hand(input: &str) -> Result<Vec<Card>, Box<dyn Error>> {
-    input
-        .trim()
-        .chars()
-        .map(|s| s.to_string().parse::<Card>())
-        .collect()
+    input.trim().chars().map(Card::try_from).collect()
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
@@ -94,37 +69,71 @@ fn hand_to_type(hand: &[Card]) -> HandType {
     }
 }
 
-fn hand_to_type_joker(hand: &[Card]) -> HandType {
-    let cards = [
-        Card::A,
-        Card::K,
-        Card::Q,
-        Card::T,
-        Card::Nine,
-        Card::Eight,
-        Card::Seven,
-        Card::Six,
-        Card::Five,
-        Card::Four,
-        Card::Three,
-        Card::Two,
-    ];
-
+/// Tries every card in place of the jokers, kept as the reference for
+/// `hand_to_type_joker`.
+#[cfg(test)]
+fn hand_to_type_joker_substitution(hand: &[Card]) -> HandType {
     let mut highest = HandType::HighCard;
 
-    for card in cards {
+    for card in Card::ALL.into_iter().filter(|&c| c != Card::J) {
         // Replace J with card.
         let updated_hand = hand
             .iter()
             .map(|&c| if c == Card::J { card } else { c })
             .collect::<Vec<_>>();
 
-        highest = max(hand_to_type(&updated_hand), highest);
+        highest = std::cmp::max(hand_to_type(&updated_hand), highest);
     }
 
     highest
 }
 
+/// Jokers always do best joining the largest group of the other cards, so count them
+/// once and promote the hand type instead of trying every substitution.
+fn hand_to_type_joker(hand: &[Card]) -> HandType {
+    let jokers = hand.iter().filter(|&&c| c == Card::J).count();
+    if jokers == 0 || jokers == hand.len() {
+        return hand_to_type(hand);
+    }
+
+    let others = hand.iter().filter(|&&c| c != Card::J).counts();
+    let (&&best, _) = others
+        .iter()
+        .max_by_key(|(_, &count)| count)
+        .expect("at least one card that isn't a joker");
+
+    let updated_hand = hand
+        .iter()
+        .map(|&c| if c == Card::J { best } else { c })
+        .collect::<Vec<_>>();
+
+    hand_to_type(&updated_hand)
+}
+
+#[test]
+fn test_hand_to_type_joker_differential() {
+    let types = |input: &str, to_type: fn(&[Card]) -> HandType| {
+        common::lines(input)
+            .iter()
+            .map(|line| {
+                parse_hand_with_bid(line)
+                    .map(|(_, hand)| to_type(&hand))
+                    .ok()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    common::differential::check(
+        "day07-joker",
+        7,
+        500,
+        20,
+        |input| types(input, hand_to_type_joker_substitution),
+        |input| types(input, hand_to_type_joker),
+    )
+    .unwrap();
+}
+
 #[test]
 fn test_hand_to_type_joker() {
     let hand = vec![Card::J, Card::J, Card::J, Card::J, Card::J];
@@ -270,45 +279,62 @@ fn parse_hand_with_bid(line: &str) -> Result<(u64, Vec<Card>), Box<dyn Error>> {
     let bid_str = parts.next().ok_or("failed to parse bid")?;
 
     let hand = parse_hand(hand_str)?;
+    if hand.len() != 5 {
+        return Err(format!("Hand must have 5 cards: {}", hand_str).into());
+    }
     let bid = bid_str.parse::<u64>()?;
 
     Ok((bid, hand))
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let lines = common::read_data("day07")?;
-
-    let hands_with_bids = lines
+fn total_winnings(hands_with_bids: &[(u64, Vec<Card>)]) -> Result<u64, Box<dyn Error>> {
+    hands_with_bids
         .iter()
-        .map(|line| parse_hand_with_bid(line))
+        .enumerate()
+        .try_fold(0u64, |total, (i, (bid, _))| {
+            let rank = i as u64 + 1;
+            bid.checked_mul(rank)?.checked_add(total)
+        })
+        .ok_or_else(|| "Total winnings too large".into())
+}
+
+fn solve(input: &Input) -> Result<(u64, u64), Box<dyn Error>> {
+    let mut hands_with_bids = input
+        .lines()
+        .map(parse_hand_with_bid)
         .collect::<Result<Vec<_>, _>>()?;
+
     // Sort the hands by strength
-    let mut hands_with_bids = hands_with_bids;
     hands_with_bids.sort_by(|(_, hand1), (_, hand2)| compare_hands(false, hand1, hand2));
+    let part1 = total_winnings(&hands_with_bids)?;
 
-    let part1 = hands_with_bids
-        .iter()
-        .enumerate()
-        .map(|(i, (bid, _))| {
-            let rank = i + 1;
-            bid * rank as u64
-        })
-        .sum::<u64>();
+    hands_with_bids.sort_by(|(_, hand1), (_, hand2)| compare_hands(true, hand1, hand2));
+    let part2 = total_winnings(&hands_with_bids)?;
 
-    println!("Part 1: {}", part1);
+    Ok((part1, part2))
+}
 
-    hands_with_bids.sort_by(|(_, hand1), (_, hand2)| compare_hands(true, hand1, hand2));
+#[test]
+fn test_crlf_input() {
+    let input = "32T3K 765\r\nT55J5 684\r\nKK677 28\r\nKTJJT 220\r\nQQQJA 483\r\n";
+    assert_eq!(solve(&Input::from_contents(input)).unwrap(), (6440, 5905));
+}
 
-    let part1 = hands_with_bids
-        .iter()
-        .enumerate()
-        .map(|(i, (bid, _))| {
-            let rank = i + 1;
-            bid * rank as u64
-        })
-        .sum::<u64>();
+#[test]
+fn test_malformed_input() {
+    assert!(solve(&Input::from_contents("32T3 765")).is_err());
+    assert!(solve(&Input::from_contents("32T3K 18446744073709551615\nKKKKK 2")).is_err());
 
-    println!("Part 2: {}", part1);
+    common::robustness::check(7, 50, 200, |input| solve(&Input::from_contents(input))).unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = Input::load("day07")?;
+
+    let (part1, part2) = solve(&input)?;
+
+    println!("Part 1: {}", part1);
+    println!("Part 2: {}", part2);
 
     Ok(())
 }
@@ -1,5 +1,10 @@
-use std::error::Error;
+use std::{collections::HashMap, error::Error, fmt};
 
+use common::{
+    graph, math,
+    parse::{Cursor, ParseError},
+    Input,
+};
 use itertools::Itertools;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -10,108 +15,243 @@ struct Direction {
     right: String,
 }
 
-fn parse_direction(line: &str) -> Option<Direction> {
-    let re = regex::Regex::new(r"(\w+) = \((\w+), (\w+)\)").ok()?;
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = ({}, {})", self.value, self.left, self.right)
+    }
+}
 
-    let caps = re.captures(line)?;
+fn parse_direction(line: &str) -> Result<Direction, ParseError> {
+    let mut cursor = Cursor::new(line);
 
-    let value = caps.get(1)?.as_str().to_string();
-    let left = caps.get(2)?.as_str().to_string();
-    let right = caps.get(3)?.as_str().to_string();
+    let value = cursor.ws().word()?.to_string();
+    cursor.ws().tag("=")?;
+    cursor.ws().tag("(")?;
+    let left = cursor.ws().word()?.to_string();
+    cursor.ws().tag(",")?;
+    let right = cursor.ws().word()?.to_string();
+    cursor.ws().tag(")")?;
+    cursor.ws().end()?;
 
-    Some(Direction { value, left, right })
+    Ok(Direction { value, left, right })
 }
 
-fn walk(guide: &str, directions: &[Direction]) -> Option<u64> {
-    // Convert directions to a hashmap.
-    let directions = directions
+type Network<'a> = HashMap<&'a str, (&'a str, &'a str)>;
+
+fn network(directions: &[Direction]) -> Network<'_> {
+    directions
         .iter()
-        .map(|d| (&d.value, (&d.left, &d.right)))
-        .collect::<std::collections::HashMap<_, _>>();
+        .map(|d| (d.value.as_str(), (d.left.as_str(), d.right.as_str())))
+        .collect()
+}
 
-    let mut current = &"AAA".to_string();
-    for (index, dir) in guide.chars().cycle().enumerate() {
-        if current == "ZZZ" {
-            return Some(index as u64);
-        }
+/// Number of steps from `start` until `end` holds, following the guide from its first
+/// instruction. Nodes are paired with the position in the guide, so once every pair has
+/// been seen the search runs dry instead of going round in circles.
+fn steps(guide: &str, network: &Network, start: &str, end: impl Fn(&str) -> bool) -> Option<u64> {
+    let guide = guide.as_bytes();
+    if guide.is_empty() {
+        return None;
+    }
+
+    let next = |&(node, index): &(&str, usize)| {
+        let (left, right) = network.get(node)?;
+        let node = match guide[index] {
+            b'L' => left,
+            b'R' => right,
+            _ => return None,
+        };
+        Some((*node, (index + 1) % guide.len()))
+    };
+
+    let path = graph::bfs_to((start, 0), next, |&(node, _)| end(node))?;
+    Some(path.len() as u64 - 1)
+}
+
+fn walk(guide: &str, directions: &[Direction]) -> Option<u64> {
+    steps(guide, &network(directions), "AAA", |node| node == "ZZZ")
+}
 
-        let (left, right) = directions.get(&current)?;
+/// When a ghost stands on a node ending in Z: at each of `prefix`, and from `start` on
+/// at every step congruent to one of `residues` modulo `period`.
+#[derive(Debug, PartialEq, Eq)]
+struct Ghost {
+    prefix: Vec<u64>,
+    start: u64,
+    period: u64,
+    residues: Vec<u64>,
+}
 
-        if dir == 'L' {
-            current = left;
-        } else if dir == 'R' {
-            current = right;
+impl Ghost {
+    fn at_end(&self, step: u64) -> bool {
+        if step < self.start {
+            self.prefix.contains(&step)
         } else {
-            return None;
+            self.residues.contains(&(step % self.period))
         }
     }
+}
+
+/// Follow a ghost from `start` until its (node, guide position) repeats, which is where
+/// its walk turns into a cycle.
+fn ghost(guide: &str, network: &Network, start: &str) -> Option<Ghost> {
+    let guide = guide.as_bytes();
+    if guide.is_empty() {
+        return None;
+    }
+
+    let mut seen = HashMap::new();
+    let mut ends = vec![];
+    let (mut node, mut index) = (start, 0);
+
+    for step in 0u64.. {
+        if let Some(&first) = seen.get(&(node, index)) {
+            let (prefix, cycle) = ends.iter().partition(|&&end| end < first);
+            let period = step - first;
+            return Some(Ghost {
+                prefix,
+                start: first,
+                period,
+                residues: cycle.into_iter().map(|end| end % period).collect(),
+            });
+        }
+        seen.insert((node, index), step);
+
+        if node.ends_with('Z') {
+            ends.push(step);
+        }
 
-    unreachable!()
+        let (left, right) = network.get(node)?;
+        node = match guide[index] {
+            b'L' => left,
+            b'R' => right,
+            _ => return None,
+        };
+        index = (index + 1) % guide.len();
+    }
+
+    None
 }
 
+/// Every combination of residues across ghosts is a candidate alignment, past this many
+/// it is cheaper to give up than to try them all.
+const MAX_ALIGNMENTS: usize = 10_000;
+
 fn walk2(guide: &str, directions: &[Direction]) -> Option<u64> {
-    // Convert directions to a hashmap.
-    let directions = directions
-        .iter()
-        .map(|d| (&d.value, (&d.left, &d.right)))
-        .collect::<std::collections::HashMap<_, _>>();
+    let network = network(directions);
 
     // Walkers are any that end in A.
-    let mut walkers = directions
+    let ghosts = network
         .keys()
         .filter(|k| k.ends_with('A'))
-        .map(Some)
-        .collect_vec();
+        .map(|start| ghost(guide, &network, start))
+        .collect::<Option<Vec<_>>>()?;
 
-    let mut path_lengths = vec![];
-
-    for (index, dir) in guide.chars().cycle().enumerate() {
-        // If all the walkers are done we are done.
-        if walkers.iter().all(|w| w.is_none()) {
-            break;
-        }
+    // Before every ghost is in its cycle we can just check each step.
+    let settled = ghosts.iter().map(|g| g.start).max()?;
+    if let Some(step) = (0..settled).find(|&step| ghosts.iter().all(|g| g.at_end(step))) {
+        return Some(step);
+    }
 
-        for walker in walkers.iter_mut() {
-            // If the waler ends in Z we are done with it, delete it from the vector.
-            if let Some(real_walker) = walker {
-                if real_walker.ends_with('Z') {
-                    *walker = None;
-                    path_lengths.push(index as u64);
-                    continue;
-                }
-
-                let (left, right) = directions.get(*real_walker)?;
-
-                if dir == 'L' {
-                    *real_walker = left;
-                } else if dir == 'R' {
-                    *real_walker = right;
-                } else {
-                    return None;
-                }
-            }
-        }
+    let alignments = ghosts
+        .iter()
+        .try_fold(1usize, |n, g| n.checked_mul(g.residues.len()))?;
+    if alignments > MAX_ALIGNMENTS {
+        return None;
     }
 
-    // Now we can find the least common multiple of all the path lengths since they are loops.
-    lcmx::lcmx(&path_lengths)
+    // After that each ghost's cycle may start at a different offset, so line them up with
+    // the CRT rather than assuming they all begin at step 0.
+    ghosts
+        .iter()
+        .map(|g| g.residues.iter().map(move |&r| (r, g.period)))
+        .multi_cartesian_product()
+        .filter_map(|congruences| {
+            let (step, period) = math::crt(&congruences)?;
+            if step >= settled {
+                return Some(step);
+            }
+            let periods = (settled - step).div_ceil(period);
+            step.checked_add(periods.checked_mul(period)?)
+        })
+        .min()
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = common::read_data("day08")?;
+#[test]
+fn test_walk2_offset_cycles() {
+    let input = common::lines(
+        "L
+11A = (11B, 11B)
+11B = (11C, 11C)
+11C = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22D, 22D)
+22D = (22Z, 22Z)",
+    );
+    let directions = input[1..]
+        .iter()
+        .map(|line| parse_direction(line))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // The first ghost ends every 3 steps from 3, the second every 4 from 1. Assuming both
+    // cycles start at 0 would give lcm(3, 1) = 3.
+    assert_eq!(walk2(&input[0], &directions), Some(9));
+}
 
-    let mut lines = input.into_iter();
+fn solve(input: &Input) -> Result<(u64, u64), Box<dyn Error>> {
+    let mut lines = input.numbered_lines();
 
-    let guide = lines.next().ok_or("No first line")?;
+    let (_, guide) = lines.next().ok_or("No first line")?;
     let directions = lines
-        .map(|line| parse_direction(&line))
-        .collect::<Option<Vec<_>>>()
-        .ok_or("Failed to parse directions")?;
+        .map(|(n, line)| parse_direction(line).map_err(|e| e.on_line(n)))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let part1 = walk(&guide, &directions).ok_or("Failed to walk")?;
-    println!("part1: {}", part1);
+    let part1 = walk(guide, &directions).ok_or("Failed to walk")?;
+    let part2 = walk2(guide, &directions).ok_or("Failed to walk")?;
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "LR
+
+AAA = (11B, XXX)
+11B = (XXX, ZZZ)
+ZZZ = (11B, XXX)
+XXX = (XXX, XXX)
+"
+    .replace('\n', "\r\n");
+
+    assert_eq!(solve(&Input::from_contents(&input)).unwrap(), (2, 2));
+}
+
+#[test]
+fn test_malformed_input() {
+    // ZZZ is never reached.
+    assert!(solve(&Input::from_contents(
+        "L\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)"
+    ))
+    .is_err());
+    assert_eq!(
+        solve(&Input::from_contents("L\n\nAAA = (ZZZ ZZZ)"))
+            .unwrap_err()
+            .to_string(),
+        "line 3, column 12: expected \",\""
+    );
+
+    common::robustness::check(8, 3, 200, |input| solve(&Input::from_contents(input))).unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let input = Input::load("day08")?;
 
-    let part2 = walk2(&guide, &directions).ok_or("Failed to walk")?;
+    let (part1, part2) = solve(&input)?;
+    println!("part1: {}", part1);
     println!("part2: {}", part2);
 
     Ok(())
@@ -128,3 +268,22 @@ fn test_parse_diirection() {
     let actual = parse_direction(example).unwrap();
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn test_round_trip() {
+    for seed in 0..20 {
+        let input = common::lines(&common::generate::generate(8, seed, 5).unwrap());
+
+        for line in &input[1..] {
+            let direction = parse_direction(line).unwrap();
+            let printed = direction.to_string();
+
+            assert_eq!(&printed, line);
+            assert_eq!(parse_direction(&printed), Ok(direction));
+        }
+    }
+
+    let direction = parse_direction("AAA=(BBB ,CCC) ").unwrap();
+    assert!(parse_direction("AAA = (BBB, CCC) # loops back").is_err());
+    assert_eq!(direction.to_string(), "AAA = (BBB, CCC)");
+}
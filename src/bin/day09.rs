@@ -1,7 +1,9 @@
-fn parse_history(line: &str) -> Option<Vec<i64>> {
-    line.split_whitespace()
-        .map(|s| s.parse::<i64>().ok())
-        .collect::<Option<Vec<i64>>>()
+use std::{error::Error, io::BufRead};
+
+use common::parse::{self, ParseError};
+
+fn parse_history(line: &str) -> Result<Vec<i64>, ParseError> {
+    parse::ints(line)
 }
 
 #[test]
@@ -11,24 +13,19 @@ fn test_parse_history() {
     assert_eq!(history, expected);
 }
 
-fn derivative(history: &[i64]) -> Vec<i64> {
-    let mut out = vec![];
-
-    for i in 0..history.len() - 1 {
-        let a = history[i];
-        let b = history[i + 1];
-
-        out.push(b - a);
-    }
-
-    out
+fn derivative(history: &[i64]) -> Option<Vec<i64>> {
+    // Empty and single value histories have an empty derivative.
+    history.windows(2).map(|w| w[1].checked_sub(w[0])).collect()
 }
 
 #[test]
 fn test_derivative() {
     let history = vec![1, 2, 3, 3];
     let expected = vec![1, 1, 0];
-    assert_eq!(derivative(&history), expected);
+    assert_eq!(derivative(&history), Some(expected));
+
+    assert_eq!(derivative(&[]), Some(vec![]));
+    assert_eq!(derivative(&[i64::MIN, 1]), None);
 }
 
 fn extrapolate(history: &[i64]) -> Option<Vec<i64>> {
@@ -36,12 +33,12 @@ fn extrapolate(history: &[i64]) -> Option<Vec<i64>> {
         return Some(vec![0]);
     }
 
-    let mut rec = extrapolate(&derivative(history))?;
+    let mut rec = extrapolate(&derivative(history)?)?;
 
     let last = history.last()?;
     let last_rec = rec.last()?;
 
-    rec.push(last + last_rec);
+    rec.push(last.checked_add(*last_rec)?);
 
     Some(rec)
 }
@@ -61,12 +58,12 @@ fn extrapolate_backwards(history: &[i64]) -> Option<Vec<i64>> {
         return Some(vec![0]);
     }
 
-    let mut rec = extrapolate_backwards(&derivative(history))?;
+    let mut rec = extrapolate_backwards(&derivative(history)?)?;
 
     let first = history.first()?;
     let first_rec = rec.first()?;
 
-    rec.insert(0, first - first_rec);
+    rec.insert(0, first.checked_sub(*first_rec)?);
 
     Some(rec)
 }
@@ -81,29 +78,58 @@ fn test_extrapolate_backwards() {
     assert_eq!(extrapolated, expected);
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input = common::read_data("day09")?;
+fn solve<R: BufRead>(mut lines: common::LineReader<R>) -> Result<(i64, i64), Box<dyn Error>> {
+    let mut part1: i64 = 0;
+    let mut part2: i64 = 0;
 
-    let part1 = input
-        .iter()
-        .filter_map(|line| parse_history(line))
-        .filter_map(|history| {
-            let extrapolated = extrapolate(&history);
-            extrapolated?.last().cloned()
-        })
-        .sum::<i64>();
+    while let Some(line) = lines.next_numbered() {
+        let (n, line) = line?;
+        let history = parse_history(line).map_err(|e| e.on_line(n))?;
 
-    println!("Part 1: {}", part1);
+        let overflow = || format!("line {}: extrapolation overflows", n);
+
+        let last = extrapolate(&history)
+            .and_then(|e| e.last().copied())
+            .ok_or_else(overflow)?;
+        part1 = part1.checked_add(last).ok_or("Part 1 sum too large")?;
+
+        let first = extrapolate_backwards(&history)
+            .and_then(|e| e.first().copied())
+            .ok_or_else(overflow)?;
+        part2 = part2.checked_add(first).ok_or("Part 2 sum too large")?;
+    }
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "0 3 6 9 12 15\r\n1 3 6 10 15 21\r\n10 13 16 21 30 45\r\n";
+    assert_eq!(
+        solve(common::LineReader::new(input.as_bytes())).unwrap(),
+        (114, 2)
+    );
+}
 
-    let part2 = input
-        .iter()
-        .filter_map(|line| parse_history(line))
-        .filter_map(|history| {
-            let extrapolated = extrapolate_backwards(&history);
-            extrapolated?.first().cloned()
-        })
-        .sum::<i64>();
+#[test]
+fn test_malformed_input() {
+    let err = solve(common::LineReader::new("1 2 3\n\n4 x 6\n".as_bytes())).unwrap_err();
+    assert_eq!(err.to_string(), "line 3, column 3: expected an integer");
+
+    let input = "1 2 3\n0 9223372036854775807 -9223372036854775808\n";
+    let err = solve(common::LineReader::new(input.as_bytes())).unwrap_err();
+    assert_eq!(err.to_string(), "line 2: extrapolation overflows");
+
+    common::robustness::check(9, 50, 200, |input| {
+        solve(common::LineReader::new(input.as_bytes()))
+    })
+    .unwrap();
+}
 
+fn main() -> Result<(), Box<dyn Error>> {
+    let (part1, part2) = solve(common::LineReader::open("day09")?)?;
+
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
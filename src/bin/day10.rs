@@ -1,35 +1,26 @@
-use std::{collections::HashSet, error::Error};
-
-#[derive(Debug, PartialEq, Eq)]
-enum Pipe {
-    Vertical,
-    Horizontal,
-    NE90,
-    NW90,
-    SE90,
-    SW90,
-    Ground,
-    Start,
-}
-
-impl TryFrom<char> for Pipe {
-    type Error = Box<dyn Error>;
-
-    fn try_from(c: char) -> Result<Self, Self::Error> {
-        match c {
-            '|' => Ok(Pipe::Vertical),
-            '-' => Ok(Pipe::Horizontal),
-            'L' => Ok(Pipe::NE90),
-            'J' => Ok(Pipe::NW90),
-            '7' => Ok(Pipe::SW90),
-            'F' => Ok(Pipe::SE90),
-            '.' => Ok(Pipe::Ground),
-            'S' => Ok(Pipe::Start),
-            _ => Err(format!("Unknown pipe: {}", c))?,
-        }
+use std::{collections::HashSet, error::Error, fmt};
+
+use common::{
+    geometry::{self, Location},
+    graph,
+};
+
+const USAGE: &str = "usage: day10 [--enclosed pick|flood|ray|check]";
+
+common::char_enum! {
+    enum Pipe {
+        Vertical = '|',
+        Horizontal = '-',
+        NE90 = 'L',
+        NW90 = 'J',
+        SE90 = 'F',
+        SW90 = '7',
+        Ground = '.',
+        Start = 'S',
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
 struct Map {
     pipes: Vec<Pipe>,
     width: usize,
@@ -51,10 +42,15 @@ impl Map {
 
         for line in input.trim().lines() {
             height += 1;
-            width = 0;
+            let row_width = line.chars().count();
+            if height > 1 && row_width != width {
+                return Err(
+                    format!("Row {} is {} wide, expected {}", height, row_width, width).into(),
+                );
+            }
+            width = row_width;
 
             for c in line.chars() {
-                width += 1;
                 pipes.push(Pipe::try_from(c)?);
             }
         }
@@ -78,89 +74,88 @@ impl Map {
         None
     }
 
-    fn walk_loop(&self, start: (usize, usize)) -> Option<Vec<(usize, usize)>> {
-        let mut to_visit = vec![(start, vec![start])];
-
-        while !to_visit.is_empty() {
-            let ((x, y), path) = to_visit.pop()?;
-
-            let Some(pipe) = self.get(x, y) else {
-                continue;
-            };
-
-            let mut possibilities = HashSet::new();
-            match pipe {
-                Pipe::Start => {
-                    if path.len() > 1 {
-                        return Some(path);
-                    }
-
-                    // We have begun, fan out in all directions.
-                    // TODO: But only if it's a valid symbol to come from.
-                    // hard code for now lmao.
-                    //possibilities.insert((x - 1, y));
-                    //possibilities.insert((x + 1, y));
-                    possibilities.insert((x, y - 1));
-                    possibilities.insert((x, y + 1));
-                }
-                Pipe::Vertical => {
-                    possibilities.insert((x, y - 1));
-                    possibilities.insert((x, y + 1));
-                }
-                Pipe::Horizontal => {
-                    possibilities.insert((x - 1, y));
-                    possibilities.insert((x + 1, y));
-                }
-                Pipe::NE90 => {
-                    possibilities.insert((x + 1, y));
-                    possibilities.insert((x, y - 1));
-                }
-                Pipe::NW90 => {
-                    possibilities.insert((x - 1, y));
-                    possibilities.insert((x, y - 1));
-                }
-                Pipe::SW90 => {
-                    possibilities.insert((x - 1, y));
-                    possibilities.insert((x, y + 1));
-                }
-                Pipe::SE90 => {
-                    possibilities.insert((x + 1, y));
-                    possibilities.insert((x, y + 1));
-                }
-                Pipe::Ground => {
-                    // Nothing
-                }
-            }
+    /// The neighbours a pipe at `(x, y)` leads to. Moving off the top or left edge wraps
+    /// to a coordinate `get` rejects rather than underflowing.
+    fn exits(&self, x: usize, y: usize, pipe: &Pipe) -> Vec<(usize, usize)> {
+        let north = (x, y.wrapping_sub(1));
+        let south = (x, y + 1);
+        let east = (x + 1, y);
+        let west = (x.wrapping_sub(1), y);
+
+        match pipe {
+            Pipe::Vertical => vec![north, south],
+            Pipe::Horizontal => vec![west, east],
+            Pipe::NE90 => vec![east, north],
+            Pipe::NW90 => vec![west, north],
+            Pipe::SW90 => vec![west, south],
+            Pipe::SE90 => vec![east, south],
+            Pipe::Ground | Pipe::Start => vec![],
+        }
+    }
 
-            for p in possibilities {
-                // Don't immediately see start as a valid path.
-                let initialized = p == start && path.len() > 2;
-                if path.contains(&p) && !initialized {
-                    continue;
-                }
+    /// The tiles `(x, y)` leads to that also lead back. The start could be any pipe so it
+    /// is tried as all of them.
+    fn connections(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let possibilities = match self.get(x, y) {
+            Some(Pipe::Start) => {
+                let mut exits = self.exits(x, y, &Pipe::Vertical);
+                exits.extend(self.exits(x, y, &Pipe::Horizontal));
+                exits
+            }
+            Some(pipe) => self.exits(x, y, pipe),
+            None => vec![],
+        };
+
+        // Junk pipes next to the loop that don't connect back would otherwise branch off it.
+        possibilities
+            .into_iter()
+            .filter(|&(px, py)| {
+                self.get(px, py).is_some_and(|next| {
+                    *next == Pipe::Start || self.exits(px, py, next).contains(&(x, y))
+                })
+            })
+            .collect()
+    }
 
-                let mut updated_path = path.clone();
-                updated_path.push(p);
+    fn walk_loop(&self, start: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        // Search over (tile, previous tile) so stepping straight back isn't a cycle. Only
+        // the start can branch, so any cycle found is the loop through it.
+        let cycle = graph::find_cycle((start, start), |&(tile, previous)| {
+            self.connections(tile.0, tile.1)
+                .into_iter()
+                .filter(move |&next| next != previous)
+                .map(move |next| (next, tile))
+        })?;
+
+        // The cycle closes wherever the search came back round, rotate it to begin and end
+        // at the start.
+        let mut path = cycle[1..].iter().map(|&(tile, _)| tile).collect::<Vec<_>>();
+        let i = path.iter().position(|&tile| tile == start)?;
+        path.rotate_left(i);
+        path.push(start);
+
+        Some(path)
+    }
 
-                to_visit.push((p, updated_path));
-            }
-        }
+    /// Tiles enclosed by the loop, by Pick's theorem on the polygon through the centres of
+    /// the loop's tiles. Every tile is a lattice point, so those strictly inside are the
+    /// enclosed tiles.
+    fn enclosed_tiles(&self, outer_path: &[(usize, usize)]) -> u64 {
+        let vertices = outer_path
+            .iter()
+            .map(|&(x, y)| (x as i64, y as i64))
+            .collect::<Vec<_>>();
 
-        None
+        geometry::interior_points(&vertices) as u64
     }
 
+    /// The enclosed tiles again by flooding the outside, slower but independent of
+    /// `enclosed_tiles`.
     fn flood_fill(&self, outer_path: &[(usize, usize)]) -> u64 {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-        enum Flood {
-            Empty,
-            Flooded,
-            Blocked,
-        }
-
         let width = self.width * 2;
         let height = self.height * 2;
 
-        let mut flood = vec![Flood::Empty; width * height];
+        let mut blocked = vec![false; width * height];
 
         // Perform flood fill, note that "squeezing between pipes" is allowed.
         // Therefor we will 2x the resolution.
@@ -174,38 +169,21 @@ impl Map {
             // fill in x1, y1 as well as the space between x1, y1 and x2, y2
             let avg_x = (x1 * 2 + x2 * 2) / 2;
             let avg_y = (y1 * 2 + y2 * 2) / 2;
-            flood[y1 * 2 * width + x1 * 2] = Flood::Blocked;
-            flood[avg_y * width + avg_x] = Flood::Blocked;
+            blocked[y1 * 2 * width + x1 * 2] = true;
+            blocked[avg_y * width + avg_x] = true;
         }
 
         // Now we flood from the outside we know 0, 0 can't be inside the path
-        let mut to_visit = vec![(0, 0)];
-        while !to_visit.is_empty() {
-            let Some((x, y)) = to_visit.pop() else {
-                continue;
-            };
-
-            let mut possibilities = HashSet::new();
-            possibilities.insert((x - 1, y));
-            possibilities.insert((x + 1, y));
-            possibilities.insert((x, y - 1));
-            possibilities.insert((x, y + 1));
-
-            for p in possibilities {
-                let (x, y) = p;
-
-                if x >= width || y >= height {
-                    continue;
-                }
-
-                if flood[y * width + x] != Flood::Empty {
-                    continue;
-                }
-
-                flood[y * width + x] = Flood::Flooded;
-                to_visit.push(p);
-            }
-        }
+        let flooded = graph::bfs((0usize, 0usize), |&(x, y)| {
+            [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ]
+            .into_iter()
+            .filter(|&(x, y)| x < width && y < height && !blocked[y * width + x])
+        });
 
         let mut total = 0;
         let original_path_set = outer_path.iter().cloned().collect::<HashSet<_>>();
@@ -215,7 +193,7 @@ impl Map {
                 if original_path_set.contains(&(x / 2, y / 2)) {
                     continue;
                 }
-                if flood[y * width + x] == Flood::Empty {
+                if !blocked[y * width + x] && !flooded.contains_key(&(x, y)) {
                     total += 1;
                 }
             }
@@ -223,6 +201,66 @@ impl Map {
 
         total / 4
     }
+
+    /// The enclosed tiles by testing every tile off the loop against the polygon, with
+    /// straight runs of the loop merged into single edges.
+    fn ray_cast(&self, outer_path: &[(usize, usize)]) -> u64 {
+        // The path comes back to the start, which would hide a corner there.
+        let path = outer_path[..outer_path.len().saturating_sub(1)]
+            .iter()
+            .map(|&(x, y)| (x as i64, y as i64))
+            .collect::<Vec<_>>();
+        let corners = path
+            .iter()
+            .enumerate()
+            .filter(|&(i, &(x, y))| {
+                let (px, py) = path[(i + path.len() - 1) % path.len()];
+                let (nx, ny) = path[(i + 1) % path.len()];
+                (x - px) * (ny - y) != (y - py) * (nx - x)
+            })
+            .map(|(_, &point)| point)
+            .collect::<Vec<_>>();
+
+        let on_path = outer_path.iter().collect::<HashSet<_>>();
+        let mut total = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !on_path.contains(&(x, y))
+                    && geometry::locate(&corners, (x as i64, y as i64)) == Location::Inside
+                {
+                    total += 1;
+                }
+            }
+        }
+
+        total
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.pipes.chunks(self.width.max(1)) {
+            let row = row.iter().map(|pipe| pipe.to_char()).collect::<String>();
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    for seed in 0..20 {
+        let input = common::generate::generate(10, seed, 20).unwrap();
+        let map = Map::parse(&input).unwrap();
+        let printed = map.to_string();
+
+        assert_eq!(printed, input);
+        assert_eq!(Map::parse(&printed).unwrap(), map);
+    }
+
+    let map = Map::parse("\n\n.S7\n.LJ\n\n").unwrap();
+    assert_eq!(map.to_string(), ".S7\n.LJ\n");
 }
 
 #[test]
@@ -264,18 +302,125 @@ fn test_map() {
     assert_eq!(path, expected);
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = std::fs::read_to_string("input/day10.txt")?;
-
-    let map = Map::parse(&input)?;
+fn solve(input: &str, enclosed: Enclosed) -> Result<(usize, u64), Box<dyn Error>> {
+    let map = Map::parse(input)?;
     let start = map.find_start().ok_or("No start found")?;
     let path = map.walk_loop(start).ok_or("No path found")?;
 
     let part1 = path.len() / 2;
-    println!("Part 1: {}", part1);
+    let part2 = enclosed(&map, &path);
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = common::Input::from_contents(".....\r\n.S-7.\r\n.|.|.\r\n.L-J.\r\n.....\r\n");
+    assert_eq!(solve(input.as_str(), Map::enclosed_tiles).unwrap(), (4, 1));
+}
+
+#[test]
+fn test_malformed_input() {
+    assert!(solve(".S-7.\n.|.|\n.L-J.", Map::enclosed_tiles).is_err());
+    // Start in the corner used to underflow.
+    assert_eq!(solve("S7\nLJ", Map::enclosed_tiles).unwrap(), (2, 0));
+
+    common::robustness::check(10, 15, 200, |input| solve(input, Map::enclosed_tiles)).unwrap();
+}
+
+#[test]
+fn test_enclosed_tiles() {
+    // Tiles between the two inner pipes are outside, reached by squeezing through.
+    let input = "
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+
+    assert_eq!(solve(input, Map::enclosed_tiles).unwrap(), (23, 4));
+    assert_eq!(solve(input, Map::flood_fill).unwrap(), (23, 4));
+    assert_eq!(solve(input, Map::ray_cast).unwrap(), (23, 4));
+}
+
+#[test]
+fn test_solve_differential() {
+    common::differential::check(
+        "day10",
+        10,
+        30,
+        50,
+        |input| solve(input, Map::flood_fill).ok(),
+        |input| solve(input, Map::enclosed_tiles).ok(),
+    )
+    .unwrap();
+    common::differential::check(
+        "day10-ray",
+        10,
+        30,
+        50,
+        |input| solve(input, Map::ray_cast).ok(),
+        |input| solve(input, Map::enclosed_tiles).ok(),
+    )
+    .unwrap();
+}
+
+type Enclosed = fn(&Map, &[(usize, usize)]) -> u64;
+
+const METHODS: [(&str, Enclosed); 3] = [
+    ("pick", Map::enclosed_tiles),
+    ("flood", Map::flood_fill),
+    ("ray", Map::ray_cast),
+];
+
+/// Solve with every method and insist they agree on the enclosed tiles.
+fn cross_check(input: &str) -> Result<(usize, u64), Box<dyn Error>> {
+    let (part1, part2) = solve(input, Map::enclosed_tiles)?;
+
+    for (name, enclosed) in &METHODS[1..] {
+        let (_, other) = solve(input, *enclosed)?;
+        if other != part2 {
+            return Err(format!(
+                "{} found {} enclosed tiles but pick found {}",
+                name, other, part2
+            )
+            .into());
+        }
+    }
+
+    Ok((part1, part2))
+}
 
-    let part2 = map.flood_fill(&path);
+#[test]
+fn test_cross_check() {
+    let input = common::generate::generate(10, 3, 20).unwrap();
+    assert_eq!(
+        cross_check(&input).unwrap(),
+        solve(&input, Map::enclosed_tiles).unwrap()
+    );
+}
 
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let method = match args.as_slice() {
+        [] => "pick",
+        [flag, method] if flag == "--enclosed" => method.as_str(),
+        _ => return Err(USAGE.into()),
+    };
+
+    let input = common::read_input("day10")?;
+
+    let (part1, part2) = match METHODS.iter().find(|(name, _)| *name == method) {
+        Some((_, enclosed)) => solve(&input, *enclosed)?,
+        None if method == "check" => cross_check(&input)?,
+        None => return Err(format!("Unknown method: {}\n{}", method, USAGE).into()),
+    };
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
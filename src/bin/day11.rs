@@ -1,5 +1,10 @@
+#[cfg(test)]
 use itertools::Itertools;
-use std::{collections::HashSet, error::Error};
+#[cfg(test)]
+use std::collections::HashSet;
+use std::{error::Error, io::BufRead};
+
+use common::answer::{Answer, Overflow};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Galaxy {
@@ -8,21 +13,25 @@ struct Galaxy {
     y: usize,
 }
 
-fn from_lines(input: Vec<String>) -> Vec<Galaxy> {
+/// Only the galaxies are kept, so the image itself is streamed rather than loaded.
+fn from_lines<R: BufRead>(mut lines: common::LineReader<R>) -> std::io::Result<Vec<Galaxy>> {
     let mut galaxies = Vec::new();
 
     let mut id = 0;
-    for (y, line) in input.iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
+    let mut y = 0;
+    while let Some(line) = lines.next_line() {
+        for (x, c) in line?.chars().enumerate() {
             if c == '#' {
                 id += 1;
                 galaxies.push(Galaxy { x, y, id });
             }
         }
+        y += 1;
     }
-    galaxies
+    Ok(galaxies)
 }
 
+#[cfg(test)]
 fn manhattan_distance(
     g1: &Galaxy,
     g2: &Galaxy,
@@ -68,13 +77,11 @@ fn manhattan_distance(
     distance
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = common::read_data("day11")?;
-
-    let galaxies = from_lines(input);
-
-    let max_y = galaxies.iter().map(|g| g.y).max().ok_or("No galaxies")?;
-    let max_x = galaxies.iter().map(|g| g.x).max().ok_or("No galaxies")?;
+/// Walks every pair step by step, kept as the reference for `total_distance`.
+#[cfg(test)]
+fn total_distance_pairwise(galaxies: &[Galaxy], expansion_amount: usize) -> usize {
+    let max_y = galaxies.iter().map(|g| g.y).max().unwrap_or(0);
+    let max_x = galaxies.iter().map(|g| g.x).max().unwrap_or(0);
 
     let y_set: HashSet<usize> = (0..=max_y).collect();
     let x_set: HashSet<usize> = (0..=max_x).collect();
@@ -85,22 +92,121 @@ fn main() -> Result<(), Box<dyn Error>> {
     let empty_rows = y_set.difference(&galaxy_ys).collect::<HashSet<_>>();
     let empty_cols = x_set.difference(&galaxy_xs).collect::<HashSet<_>>();
 
-    // Now for each pair of galaxies find the manhattan distance, if we cross an empty row or column we expand the distance by 1.
-    let part1 = galaxies
+    // Now for each pair of galaxies find the manhattan distance, if we cross an empty row or column we expand the distance.
+    galaxies
         .iter()
         .combinations(2)
-        .map(|g| manhattan_distance(g[0], g[1], &empty_rows, &empty_cols, 2))
-        .sum::<usize>();
+        .map(|g| manhattan_distance(g[0], g[1], &empty_rows, &empty_cols, expansion_amount))
+        .sum::<usize>()
+}
 
-    println!("Part 1: {}", part1);
+/// Sum of the distances between every pair of positions along one axis. Positions are
+/// first shifted by the empty lines before them, then sorted so each one contributes
+/// its distance to everything before it via a running prefix sum.
+fn axis_distance(mut positions: Vec<usize>, expansion_amount: usize) -> Result<Answer, Overflow> {
+    positions.sort_unstable();
+
+    let mut total = Answer::ZERO;
+    let mut prefix = Answer::ZERO;
+    let mut occupied = 0;
+    let mut previous = None;
+
+    for (i, &position) in positions.iter().enumerate() {
+        if previous != Some(position) {
+            occupied += 1;
+            previous = Some(position);
+        }
+
+        let empty_before = position + 1 - occupied;
+        let expanded = Answer::from(empty_before)
+            .checked_mul(expansion_amount.saturating_sub(1))?
+            .checked_add(position)?;
+
+        // Sorted, so this galaxy is at least as far along as every one before it.
+        let to_previous = expanded.checked_mul(i)?.0 - prefix.0;
+        total = total.checked_add(to_previous)?;
+        prefix = prefix.checked_add(expanded)?;
+    }
+
+    Ok(total)
+}
+
+fn total_distance(galaxies: &[Galaxy], expansion_amount: usize) -> Result<Answer, Overflow> {
+    let xs = galaxies.iter().map(|g| g.x).collect();
+    let ys = galaxies.iter().map(|g| g.y).collect();
+
+    axis_distance(xs, expansion_amount)?.checked_add(axis_distance(ys, expansion_amount)?)
+}
+
+#[test]
+fn test_total_distance_differential() {
+    let solve = |input: &str, total: fn(&[Galaxy], usize) -> Result<Answer, Overflow>| {
+        let galaxies = from_lines(common::LineReader::new(input.as_bytes())).ok()?;
+        Some((total(&galaxies, 2).ok()?, total(&galaxies, 1_000_000).ok()?))
+    };
+
+    common::differential::check(
+        "day11",
+        11,
+        60,
+        20,
+        |input| {
+            solve(input, |galaxies, expansion| {
+                Ok(Answer::from(total_distance_pairwise(galaxies, expansion)))
+            })
+        },
+        |input| solve(input, total_distance),
+    )
+    .unwrap();
+}
+
+fn solve<R: BufRead>(lines: common::LineReader<R>) -> Result<(Answer, Answer), Box<dyn Error>> {
+    let galaxies = from_lines(lines)?;
+    if galaxies.is_empty() {
+        return Err("No galaxies".into());
+    }
+
+    let part1 = total_distance(&galaxies, 2)?;
 
     // Now part 2 we expand by 1 million.
-    let part2 = galaxies
-        .iter()
-        .combinations(2)
-        .map(|g| manhattan_distance(g[0], g[1], &empty_rows, &empty_cols, 1_000_000))
-        .sum::<usize>();
+    let part2 = total_distance(&galaxies, 1_000_000)?;
+
+    Ok((part1, part2))
+}
+
+#[test]
+fn test_crlf_input() {
+    let input = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....
+"
+    .replace('\n', "\r\n");
+
+    assert_eq!(
+        solve(common::LineReader::new(input.as_bytes())).unwrap(),
+        (Answer(374), Answer(82000210))
+    );
+}
+
+#[test]
+fn test_malformed_input() {
+    common::robustness::check(11, 40, 200, |input| {
+        solve(common::LineReader::new(input.as_bytes()))
+    })
+    .unwrap();
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (part1, part2) = solve(common::LineReader::open("day11")?)?;
 
+    println!("Part 1: {}", part1);
     println!("Part 2: {}", part2);
 
     Ok(())
@@ -0,0 +1,257 @@
+//! ChaCha20-Poly1305 authenticated encryption (RFC 8439), written against std only so
+//! inputs can be kept encrypted without pulling in a crypto crate.
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+/// One 64 byte block of keystream.
+fn chacha20_block(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; NONCE_LEN]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    for i in 0..8 {
+        state[4 + i] = le32(&key[i * 4..]);
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = le32(&nonce[i * 4..]);
+    }
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for (i, word) in working.iter().enumerate() {
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.wrapping_add(state[i]).to_le_bytes());
+    }
+    out
+}
+
+/// XOR `data` with the keystream starting at block `counter`. Encrypts and decrypts.
+pub fn chacha20(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; NONCE_LEN], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let block = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, k) in chunk.iter_mut().zip(block) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// Poly1305 one-time authenticator over `message`, using 26 bit limbs so every product
+/// fits in a `u64`.
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LEN] {
+    const MASK: u32 = 0x3ff_ffff;
+
+    // r with the clamping from the spec folded into the masks.
+    let r0 = le32(&key[0..]) & 0x3ff_ffff;
+    let r1 = (le32(&key[3..]) >> 2) & 0x3ff_ff03;
+    let r2 = (le32(&key[6..]) >> 4) & 0x3ff_c0ff;
+    let r3 = (le32(&key[9..]) >> 6) & 0x3f0_3fff;
+    let r4 = (le32(&key[12..]) >> 8) & 0x00f_ffff;
+    let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+    let mut h = [0u32; 5];
+
+    for chunk in message.chunks(16) {
+        // Short final blocks get their 1 bit appended as a byte instead of bit 128.
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        let hibit = if chunk.len() == 16 { 1 << 24 } else { 0 };
+
+        h[0] += le32(&block[0..]) & MASK;
+        h[1] += (le32(&block[3..]) >> 2) & MASK;
+        h[2] += (le32(&block[6..]) >> 4) & MASK;
+        h[3] += (le32(&block[9..]) >> 6) & MASK;
+        h[4] += (le32(&block[12..]) >> 8) | hibit;
+
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+        let (r0, r1, r2, r3, r4) = (r0 as u64, r1 as u64, r2 as u64, r3 as u64, r4 as u64);
+        let (s1, s2, s3, s4) = (s1 as u64, s2 as u64, s3 as u64, s4 as u64);
+
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        d1 += d0 >> 26;
+        h[0] = d0 as u32 & MASK;
+        d2 += d1 >> 26;
+        h[1] = d1 as u32 & MASK;
+        d3 += d2 >> 26;
+        h[2] = d2 as u32 & MASK;
+        d4 += d3 >> 26;
+        h[3] = d3 as u32 & MASK;
+        h[0] += (d4 >> 26) as u32 * 5;
+        h[4] = d4 as u32 & MASK;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    // Fully carry h, then subtract p = 2^130 - 5 if h >= p.
+    for i in 1..5 {
+        h[i] += h[i - 1] >> 26;
+        h[i - 1] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for i in 0..5 {
+        g[i] = h[i] + carry;
+        carry = g[i] >> 26;
+        g[i] &= MASK;
+    }
+    // Bit 26 of the top limb is set exactly when h + 5 >= 2^130, i.e. h >= p.
+    if carry == 1 {
+        h = g;
+    }
+
+    // Pack into 128 bits and add s, the second half of the key.
+    let h = h[0] as u128
+        | (h[1] as u128) << 26
+        | (h[2] as u128) << 52
+        | (h[3] as u128) << 78
+        | (h[4] as u128) << 104;
+    let s = u128::from_le_bytes(key[16..].try_into().expect("16 byte half key"));
+
+    h.wrapping_add(s).to_le_bytes()
+}
+
+fn mac_data(aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let pad = |len: usize| vec![0; (16 - len % 16) % 16];
+
+    let mut data = aad.to_vec();
+    data.extend(pad(aad.len()));
+    data.extend(ciphertext);
+    data.extend(pad(ciphertext.len()));
+    data.extend((aad.len() as u64).to_le_bytes());
+    data.extend((ciphertext.len() as u64).to_le_bytes());
+    data
+}
+
+fn one_time_key(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN]) -> [u8; 32] {
+    let block = chacha20_block(key, 0, nonce);
+    block[..32].try_into().expect("32 byte one time key")
+}
+
+/// Encrypt `plaintext` and authenticate it along with `aad`, returning the ciphertext
+/// followed by the tag.
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    chacha20(key, 1, nonce, &mut out);
+
+    let tag = poly1305(&one_time_key(key, nonce), &mac_data(aad, &out));
+    out.extend(tag);
+    out
+}
+
+/// Check the tag on `sealed` and decrypt it, `None` if it was tampered with or the key,
+/// nonce or `aad` differ from those it was sealed with.
+pub fn open(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    let split = sealed.len().checked_sub(TAG_LEN)?;
+    let (ciphertext, tag) = sealed.split_at(split);
+
+    let expected = poly1305(&one_time_key(key, nonce), &mac_data(aad, ciphertext));
+    // Compare every byte so the time taken doesn't reveal how much of the tag matched.
+    let difference = expected
+        .iter()
+        .zip(tag)
+        .fold(0, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return None;
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    chacha20(key, 1, nonce, &mut plaintext);
+    Some(plaintext)
+}
+
+#[cfg(test)]
+fn hex(s: &str) -> Vec<u8> {
+    let s = s.split_whitespace().collect::<String>();
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_chacha20_block() {
+    // RFC 8439 section 2.3.2.
+    let key = (0..32).collect::<Vec<u8>>().try_into().unwrap();
+    let nonce = hex("000000090000004a00000000").try_into().unwrap();
+
+    let expected = hex(
+        "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+         d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+    );
+    assert_eq!(chacha20_block(&key, 1, &nonce).to_vec(), expected);
+}
+
+#[test]
+fn test_poly1305() {
+    // RFC 8439 section 2.5.2.
+    let key = hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+        .try_into()
+        .unwrap();
+
+    let tag = poly1305(&key, b"Cryptographic Forum Research Group");
+    assert_eq!(tag.to_vec(), hex("a8061dc1305136c6c22b8baf0c0127a9"));
+}
+
+#[test]
+fn test_seal_and_open() {
+    // RFC 8439 section 2.8.2.
+    let key = (0x80..0xa0).collect::<Vec<u8>>().try_into().unwrap();
+    let nonce = hex("070000004041424344454647").try_into().unwrap();
+    let aad = hex("50515253c0c1c2c3c4c5c6c7");
+    let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    let sealed = seal(&key, &nonce, &aad, plaintext);
+    assert_eq!(&sealed[..8], hex("d31a8d34648e60db"));
+    assert_eq!(
+        &sealed[sealed.len() - TAG_LEN..],
+        hex("1ae10b594f09e26a7e902ecbd0600691")
+    );
+    assert_eq!(open(&key, &nonce, &aad, &sealed).unwrap(), plaintext);
+
+    let mut tampered = sealed.clone();
+    tampered[3] ^= 1;
+    assert_eq!(open(&key, &nonce, &aad, &tampered), None);
+    assert_eq!(open(&key, &nonce, b"other", &sealed), None);
+    assert_eq!(open(&key, &nonce, &aad, &sealed[..10]), None);
+}
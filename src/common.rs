@@ -1,12 +1,413 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+};
 
-pub fn read_data(day: &str) -> Result<Vec<String>, Box<dyn Error>> {
-    let filename = format!("input/{}.txt", day);
-    let contents = std::fs::read_to_string(filename)?;
-    let lines: Vec<String> = contents
+pub mod aho_corasick;
+pub mod answer;
+pub mod cipher;
+pub mod differential;
+pub mod embedded;
+pub mod generate;
+pub mod geometry;
+pub mod graph;
+pub mod math;
+pub mod parse;
+pub mod ranges;
+pub mod rng;
+pub mod robustness;
+pub mod store;
+
+/// Controls how raw input files are cleaned up before any day sees them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalize {
+    /// Drop a leading UTF-8 byte order mark.
+    pub strip_bom: bool,
+    /// Turn "\r\n" (and lone "\r") line endings into "\n".
+    pub crlf: bool,
+    /// Remove spaces and tabs at the end of every line.
+    pub trim_trailing: bool,
+}
+
+impl Default for Normalize {
+    fn default() -> Self {
+        Normalize {
+            strip_bom: true,
+            crlf: true,
+            trim_trailing: true,
+        }
+    }
+}
+
+impl Normalize {
+    /// Leave the input exactly as it was read.
+    pub fn none() -> Self {
+        Normalize {
+            strip_bom: false,
+            crlf: false,
+            trim_trailing: false,
+        }
+    }
+}
+
+pub fn normalize(contents: &str, opts: &Normalize) -> String {
+    let mut contents = contents;
+    if opts.strip_bom {
+        contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    }
+
+    let contents = if opts.crlf {
+        contents.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        contents.to_string()
+    };
+
+    if !opts.trim_trailing {
+        return contents;
+    }
+
+    contents
+        .split('\n')
+        .map(|line| line.trim_end_matches([' ', '\t']))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_normalize() {
+    let opts = Normalize::default();
+
+    assert_eq!(normalize("\u{feff}a\r\nb  \r\n", &opts), "a\nb\n");
+    assert_eq!(normalize("a\rb\t\n", &opts), "a\nb\n");
+    assert_eq!(normalize("  a  \n", &opts), "  a\n");
+
+    let raw = "\u{feff}a \r\n";
+    assert_eq!(normalize(raw, &Normalize::none()), raw);
+
+    let crlf_only = Normalize {
+        strip_bom: false,
+        crlf: true,
+        trim_trailing: false,
+    };
+    assert_eq!(normalize(raw, &crlf_only), "\u{feff}a \n");
+}
+
+/// Split normalized contents into non-empty lines.
+pub fn lines(contents: &str) -> Vec<String> {
+    normalize(contents, &Normalize::default())
         .split('\n')
         .map(|s| s.to_string())
         .filter(|s| !s.is_empty())
-        .collect();
-    Ok(lines)
+        .collect()
+}
+
+#[test]
+fn test_lines() {
+    assert_eq!(lines("a\r\n\r\nb\r\n"), vec!["a", "b"]);
+}
+
+/// Where a day's input lives, `$AOC_INPUT_DIR` overrides the default `input/` directory
+/// so the binaries can be pointed at generated stress inputs.
+pub fn input_path(day: &str) -> PathBuf {
+    let dir = std::env::var_os("AOC_INPUT_DIR").unwrap_or_else(|| "input".into());
+    PathBuf::from(dir).join(format!("{}.txt", day))
+}
+
+/// A day's raw input when there is no plain file: the decrypted `store` copy, else the
+/// one embedded by the `embed-inputs` feature, else the original `NotFound` error.
+fn read_fallback(day: &str, not_found: io::Error) -> Result<String, Box<dyn Error>> {
+    if let Some(contents) = store::read(day)? {
+        return Ok(contents);
+    }
+    Ok(embedded::get(day).ok_or(not_found)?.to_string())
+}
+
+/// Read the whole input for a day with the given normalization applied. Without an input
+/// file this falls back to the encrypted copy, then the embedded one.
+pub fn read_input_with(day: &str, opts: &Normalize) -> Result<String, Box<dyn Error>> {
+    let contents = match std::fs::read_to_string(input_path(day)) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => read_fallback(day, e)?,
+        contents => contents?,
+    };
+
+    Ok(normalize(&contents, opts))
+}
+
+/// Read the whole input for a day, for puzzles that care about blank lines.
+pub fn read_input(day: &str) -> Result<String, Box<dyn Error>> {
+    read_input_with(day, &Normalize::default())
+}
+
+pub fn read_data(day: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(lines(&read_input(day)?))
+}
+
+/// A day's input read once, handing out lines and sections that borrow from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    contents: String,
+}
+
+impl Input {
+    pub fn load(day: &str) -> Result<Input, Box<dyn Error>> {
+        Self::load_with(day, &Normalize::default())
+    }
+
+    pub fn load_with(day: &str, opts: &Normalize) -> Result<Input, Box<dyn Error>> {
+        Ok(Input {
+            contents: read_input_with(day, opts)?,
+        })
+    }
+
+    pub fn from_contents(contents: &str) -> Input {
+        Input {
+            contents: normalize(contents, &Normalize::default()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.contents
+    }
+
+    /// Non-empty lines, borrowed from the contents rather than copied out one by one.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.numbered_lines().map(|(_, line)| line)
+    }
+
+    /// Like `lines`, along with each line's 1-based number counting blank lines.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.contents
+            .split('\n')
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| (i + 1, line))
+    }
+
+    /// Blocks of lines separated by one or more blank lines.
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.numbered_sections().map(|(_, section)| section)
+    }
+
+    /// Like `sections`, along with the 1-based number of each section's first line.
+    pub fn numbered_sections(&self) -> impl Iterator<Item = (usize, &str)> {
+        let mut line = 1;
+
+        self.contents.split("\n\n").filter_map(move |section| {
+            let start = line + section.len() - section.trim_start_matches('\n').len();
+            line += section.matches('\n').count() + 2;
+
+            let section = section.trim_matches('\n');
+            (!section.is_empty()).then_some((start, section))
+        })
+    }
+}
+
+#[test]
+fn test_input() {
+    let input = Input::from_contents("seeds: 1 2\r\n\r\n\r\na:\r\n1\r\n\r\nb:\r\n2\r\n");
+
+    assert_eq!(
+        input.lines().collect::<Vec<_>>(),
+        vec!["seeds: 1 2", "a:", "1", "b:", "2"]
+    );
+    assert_eq!(
+        input.sections().collect::<Vec<_>>(),
+        vec!["seeds: 1 2", "a:\n1", "b:\n2"]
+    );
+    assert_eq!(
+        input
+            .numbered_sections()
+            .map(|(n, _)| n)
+            .collect::<Vec<_>>(),
+        vec![1, 4, 7]
+    );
+    assert_eq!(input.numbered_lines().nth(1), Some((4, "a:")));
+}
+
+/// Streams non-empty lines from a reader one at a time, reusing a single buffer, for
+/// inputs too large to hold in memory. Lone '\r' line endings are not split on.
+pub struct LineReader<R> {
+    reader: R,
+    buf: String,
+    opts: Normalize,
+    /// Lines read so far, blank ones included.
+    line: usize,
+}
+
+impl LineReader<Box<dyn BufRead>> {
+    /// Stream a day's input file. Without one, the encrypted or embedded copy is held in
+    /// memory and streamed from there.
+    pub fn open(day: &str) -> Result<Self, Box<dyn Error>> {
+        let reader: Box<dyn BufRead> = match File::open(input_path(day)) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Box::new(io::Cursor::new(read_fallback(day, e)?.into_bytes()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(LineReader::new(reader))
+    }
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_normalize(reader, Normalize::default())
+    }
+
+    pub fn with_normalize(reader: R, opts: Normalize) -> Self {
+        LineReader {
+            reader,
+            buf: String::new(),
+            opts,
+            line: 0,
+        }
+    }
+
+    /// The next non-empty line, or `None` at the end of the input.
+    pub fn next_line(&mut self) -> Option<io::Result<&str>> {
+        self.next_numbered().map(|line| line.map(|(_, line)| line))
+    }
+
+    /// Like `next_line`, along with the line's 1-based number counting blank lines.
+    pub fn next_numbered(&mut self) -> Option<io::Result<(usize, &str)>> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(e)),
+            }
+
+            // Work out the bounds first so the returned slice is the only borrow of `buf`.
+            let mut line = self.buf.strip_suffix('\n').unwrap_or(&self.buf);
+            let mut start = 0;
+            if self.line == 1 && self.opts.strip_bom {
+                if let Some(rest) = line.strip_prefix('\u{feff}') {
+                    start = line.len() - rest.len();
+                    line = rest;
+                }
+            }
+            if self.opts.crlf {
+                line = line.strip_suffix('\r').unwrap_or(line);
+            }
+            if self.opts.trim_trailing {
+                line = line.trim_end_matches([' ', '\t']);
+            }
+            let end = start + line.len();
+
+            if start != end {
+                return Some(Ok((self.line, &self.buf[start..end])));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_line_reader() {
+    let mut reader = LineReader::new("\u{feff}a \r\n\r\nbc\r\nd".as_bytes());
+
+    let mut lines = vec![];
+    while let Some(line) = reader.next_numbered() {
+        let (number, line) = line.unwrap();
+        lines.push((number, line.to_string()));
+    }
+
+    assert_eq!(
+        lines,
+        vec![
+            (1, "a".to_string()),
+            (3, "bc".to_string()),
+            (4, "d".to_string())
+        ]
+    );
+}
+
+/// Declare an enum where every variant stands for one character. Along with the enum this
+/// generates `TryFrom<char>`, `Display` writing the character back out, `to_char`, an
+/// `ALL` array of the variants and ordering, all in declaration order.
+///
+/// ```
+/// common::char_enum! {
+///     enum Tile {
+///         Open = '.',
+///         Wall = '#',
+///     }
+/// }
+///
+/// assert_eq!(Tile::try_from('#').unwrap(), Tile::Wall);
+/// assert_eq!(Tile::Open.to_string(), ".");
+/// assert!(Tile::Open < Tile::Wall);
+/// ```
+#[macro_export]
+macro_rules! char_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $c:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant),+
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            pub const ALL: [$name; [$($c),+].len()] = [$($name::$variant),+];
+
+            pub fn to_char(self) -> char {
+                match self {
+                    $($name::$variant => $c),+
+                }
+            }
+        }
+
+        impl TryFrom<char> for $name {
+            type Error = Box<dyn std::error::Error>;
+
+            fn try_from(c: char) -> Result<Self, Self::Error> {
+                match c {
+                    $($c => Ok($name::$variant),)+
+                    _ => Err(format!("Invalid {}: {:?}", stringify!($name), c).into()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.to_char())
+            }
+        }
+    };
+}
+
+#[test]
+fn test_char_enum() {
+    crate::char_enum! {
+        enum Suit {
+            Clubs = 'c',
+            Diamonds = 'd',
+            Hearts = 'h',
+            Spades = 's',
+        }
+    }
+
+    assert_eq!(
+        Suit::ALL,
+        [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades]
+    );
+    assert!(Suit::Clubs < Suit::Spades);
+
+    for suit in Suit::ALL {
+        assert_eq!(Suit::try_from(suit.to_char()).unwrap(), suit);
+    }
+    let printed = Suit::ALL.iter().map(|s| s.to_string()).collect::<String>();
+    assert_eq!(printed, "cdhs");
+
+    let err = Suit::try_from('x').unwrap_err();
+    assert_eq!(err.to_string(), "Invalid Suit: 'x'");
 }
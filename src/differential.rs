@@ -0,0 +1,125 @@
+//! Differential testing: run a slow, obviously correct reference and an optimized
+//! solution over many generated inputs and insist they agree.
+
+use std::{
+    error::Error,
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+};
+
+use crate::generate::generate;
+
+/// Where minimized failing inputs are written.
+pub const FAILURE_DIR: &str = "target/differential";
+
+/// Run `f`, turning a panic into `None` so a crashing side counts as a disagreement
+/// rather than aborting the whole run.
+fn run<T>(f: &impl Fn(&str) -> T, input: &str) -> Option<T> {
+    panic::catch_unwind(AssertUnwindSafe(|| f(input))).ok()
+}
+
+/// Check `reference` and `optimized` agree on `cases` generated inputs for `day`, using
+/// seeds `0..cases`. On the first disagreement the input is minimized, saved under
+/// `FAILURE_DIR` as `<name>-seed<N>.txt` and an error describing both answers is returned.
+pub fn check<T, R, O>(
+    name: &str,
+    day: u32,
+    size: usize,
+    cases: u64,
+    reference: R,
+    optimized: O,
+) -> Result<(), Box<dyn Error>>
+where
+    T: PartialEq + Debug,
+    R: Fn(&str) -> T,
+    O: Fn(&str) -> T,
+{
+    let disagree = |input: &str| run(&reference, input) != run(&optimized, input);
+
+    for seed in 0..cases {
+        let input = generate(day, seed, size)?;
+        if !disagree(&input) {
+            continue;
+        }
+
+        let minimized = minimize(&input, disagree);
+        let path = save(name, seed, &minimized)?;
+
+        return Err(format!(
+            "{}: reference and optimized disagree on seed {} (minimized input in {})\n  reference: {:?}\n  optimized: {:?}",
+            name,
+            seed,
+            path.display(),
+            run(&reference, &minimized),
+            run(&optimized, &minimized),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn save(name: &str, seed: u64, input: &str) -> Result<PathBuf, Box<dyn Error>> {
+    std::fs::create_dir_all(FAILURE_DIR)?;
+    let path = PathBuf::from(FAILURE_DIR).join(format!("{}-seed{}.txt", name, seed));
+    std::fs::write(&path, input)?;
+    Ok(path)
+}
+
+/// Line based delta debugging: repeatedly drop chunks of lines, halving the chunk size,
+/// as long as `fails` still holds for what is left.
+pub fn minimize(input: &str, fails: impl Fn(&str) -> bool) -> String {
+    let mut lines = input.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+    let join = |lines: &[String]| lines.join("\n") + "\n";
+
+    let mut chunk = lines.len().div_ceil(2).max(1);
+    loop {
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + chunk).min(lines.len());
+
+            let mut candidate = lines[..start].to_vec();
+            candidate.extend_from_slice(&lines[end..]);
+
+            if !candidate.is_empty() && fails(&join(&candidate)) {
+                lines = candidate;
+            } else {
+                start = end;
+            }
+        }
+
+        if chunk == 1 {
+            break;
+        }
+        chunk = chunk.div_ceil(2);
+    }
+
+    join(&lines)
+}
+
+#[test]
+fn test_minimize() {
+    let input = "a\nb\nbad\nc\nd\nworse\ne\n";
+
+    let minimized = minimize(input, |s| s.contains("bad") && s.contains("worse"));
+    assert_eq!(minimized, "bad\nworse\n");
+}
+
+#[test]
+fn test_check() {
+    let count = |input: &str| input.lines().count();
+    let newlines = |input: &str| input.matches('\n').count();
+    assert!(check("lines", 9, 10, 5, count, newlines).is_ok());
+
+    // Disagrees as soon as a history has a negative value.
+    let err = check("negative", 9, 10, 5, count, |input| {
+        input.lines().filter(|l| !l.contains('-')).count()
+    })
+    .unwrap_err();
+
+    let saved = std::fs::read_to_string(format!("{}/negative-seed0.txt", FAILURE_DIR)).unwrap();
+    assert_eq!(saved.lines().count(), 1);
+    assert!(saved.contains('-'));
+    assert!(err.to_string().contains("seed 0"));
+}
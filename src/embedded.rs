@@ -0,0 +1,44 @@
+//! Puzzle inputs compiled into the binaries with the `embed-inputs` feature, so a build
+//! can be run from anywhere without `input/` next to it.
+//!
+//! The plain `input/dayNN.txt` files are embedded, so the binaries never need
+//! `AOC_INPUT_KEY`. They have to exist at build time: if the inputs were moved to the
+//! encrypted store, run `advent-of-code-2023 decrypt` before building. `ci/embed-inputs.sh`
+//! checks that every day runs from an empty directory.
+
+#[cfg(feature = "embed-inputs")]
+macro_rules! embed {
+    ($($day:literal),+ $(,)?) => {
+        [$(($day, include_str!(concat!("../input/", $day, ".txt")))),+]
+    };
+}
+
+#[cfg(feature = "embed-inputs")]
+const INPUTS: [(&str, &str); 11] = embed!(
+    "day01", "day02", "day03", "day04", "day05", "day06", "day07", "day08", "day09", "day10",
+    "day11",
+);
+
+/// The input for `day` as it was when the binary was built, or `None` if inputs weren't
+/// embedded.
+#[cfg(feature = "embed-inputs")]
+pub fn get(day: &str) -> Option<&'static str> {
+    INPUTS
+        .iter()
+        .find(|(name, _)| *name == day)
+        .map(|(_, contents)| *contents)
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+pub fn get(_day: &str) -> Option<&'static str> {
+    None
+}
+
+#[test]
+fn test_get() {
+    let file = std::fs::read_to_string("input/day01.txt").unwrap();
+    let expected = cfg!(feature = "embed-inputs").then_some(file.as_str());
+
+    assert_eq!(get("day01"), expected);
+    assert_eq!(get("day99"), None);
+}
@@ -0,0 +1,585 @@
+//! Structurally valid puzzle inputs for every day, so we can test and stress the
+//! solutions without sharing the real inputs.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use crate::rng::Rng;
+
+const NUMBER_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+
+const CARDS: [char; 13] = [
+    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
+];
+
+const ALMANAC_MAPS: [&str; 7] = [
+    "seed-to-soil",
+    "soil-to-fertilizer",
+    "fertilizer-to-water",
+    "water-to-light",
+    "light-to-temperature",
+    "temperature-to-humidity",
+    "humidity-to-location",
+];
+
+/// Write the input for `day` to `out`, `size` scales the input in whatever way makes
+/// sense for that puzzle (lines, grid width, map rows, ...). Line-based days write as
+/// they go, so inputs far larger than memory can be streamed to a file.
+pub fn write(day: u32, seed: u64, size: usize, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let mut rng = Rng::new(seed);
+    let rng = &mut rng;
+
+    match day {
+        1 => day01(rng, size, out)?,
+        2 => day02(rng, size, out)?,
+        3 => day03(rng, size, out)?,
+        4 => day04(rng, size, out)?,
+        5 => day05(rng, size, out)?,
+        6 => day06(rng, size, out)?,
+        7 => day07(rng, size, out)?,
+        8 => day08(rng, size, out)?,
+        9 => day09(rng, size, out)?,
+        10 => day10(rng, size, out)?,
+        11 => day11(rng, size, out)?,
+        _ => return Err(format!("No generator for day {}", day).into()),
+    }
+
+    Ok(())
+}
+
+/// The input `write` would give, held in memory.
+pub fn generate(day: u32, seed: u64, size: usize) -> Result<String, Box<dyn Error>> {
+    let mut out = vec![];
+    write(day, seed, size, &mut out)?;
+
+    Ok(String::from_utf8(out)?)
+}
+
+fn letter(rng: &mut Rng) -> char {
+    (b'a' + rng.range(0, 26) as u8) as char
+}
+
+/// `size` calibration lines mixing letters, digits and spelled out digits. Every line
+/// has at least one real digit so part 1 is defined.
+pub fn day01(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    for _ in 0..size {
+        let mut line = String::new();
+        let digit_at = rng.range(0, 6);
+
+        for token in 0..rng.range(digit_at + 1, 10) {
+            match rng.range(0, 3) {
+                _ if token == digit_at => line.push((b'1' + rng.range(0, 9) as u8) as char),
+                0 => line.push_str(rng.choose::<&str>(&NUMBER_WORDS)),
+                1 => line.push((b'1' + rng.range(0, 9) as u8) as char),
+                _ => {
+                    for _ in 0..rng.range(1, 5) {
+                        line.push(letter(rng));
+                    }
+                }
+            }
+        }
+
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+/// `size` games of one to six hands, each hand showing up to three distinct colors.
+pub fn day02(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    for id in 1..=size {
+        let hands = (0..rng.range(1, 7))
+            .map(|_| {
+                let mut colors = COLORS.to_vec();
+                rng.shuffle(&mut colors);
+                colors.truncate(rng.range(1, 4) as usize);
+
+                colors
+                    .iter()
+                    .map(|color| format!("{} {}", rng.range(1, 21), color))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>();
+
+        writeln!(out, "Game {}: {}", id, hands.join("; "))?;
+    }
+
+    Ok(())
+}
+
+/// A `size` x `size` schematic of numbers and symbols on a '.' background.
+pub fn day03(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    let width = size.max(1);
+
+    for _ in 0..width {
+        let mut row = String::new();
+
+        while row.len() < width {
+            let digits = rng.range(1, 4) as usize;
+
+            if rng.one_in(6) && row.len() + digits < width {
+                write!(
+                    row,
+                    "{}",
+                    rng.range(10u64.pow(digits as u32 - 1), 10u64.pow(digits as u32))
+                )
+                .unwrap();
+                // Numbers on a row are always separated.
+                row.push('.');
+            } else if rng.one_in(10) {
+                row.push(*rng.choose(&SYMBOLS));
+            } else {
+                row.push('.');
+            }
+        }
+
+        writeln!(out, "{}", &row[..width])?;
+    }
+
+    Ok(())
+}
+
+fn distinct_numbers(rng: &mut Rng) -> Vec<u64> {
+    let mut numbers = (1..100).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+    numbers
+}
+
+/// `size` scratchcards with 10 winning numbers and 25 numbers you have.
+pub fn day04(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    let id_width = size.to_string().len();
+
+    for id in 1..=size {
+        let numbers = distinct_numbers(rng);
+        let winning = &numbers[..10];
+
+        // Keep matches rare so the part 2 copy counts stay bounded as the input grows.
+        let matches = if rng.one_in(5) {
+            rng.range(1, 11).min(rng.range(1, 11)) as usize
+        } else {
+            0
+        };
+        let mut have = winning[..matches].to_vec();
+        have.extend(&numbers[10..10 + 25 - matches]);
+        rng.shuffle(&mut have);
+
+        let join = |numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|n| format!("{:>2}", n))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(
+            out,
+            "Card {:>width$}: {} | {}",
+            id,
+            join(winning),
+            join(&have),
+            width = id_width
+        )?;
+    }
+
+    Ok(())
+}
+
+/// An almanac with ten seed ranges and seven maps of `size` rows each. Source ranges
+/// within a map never overlap.
+pub fn day05(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    const LIMIT: u64 = 1 << 32;
+    let rows = size.max(1);
+
+    write!(out, "seeds:")?;
+    for _ in 0..10 {
+        let start = rng.range(0, LIMIT / 2);
+        let length = rng.range(1, 100 * rows as u64 + 1);
+        write!(out, " {} {}", start, length)?;
+    }
+    writeln!(out)?;
+
+    for name in ALMANAC_MAPS {
+        write!(out, "\n{} map:\n", name)?;
+
+        let mut cuts = (0..rows * 2)
+            .map(|_| rng.range(0, LIMIT))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if cuts.len() % 2 == 1 {
+            cuts.pop();
+        }
+
+        let mut ranges = cuts
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1] - pair[0]))
+            .filter(|(_, length)| *length > 0)
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut ranges);
+
+        for (source, length) in ranges {
+            let dest = rng.range(0, LIMIT - length);
+            writeln!(out, "{} {} {}", dest, source, length)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Between one and four races (part 2 concatenates them so more would not fit), each
+/// with a record that can be beaten.
+pub fn day06(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    let races = size.clamp(1, 4);
+
+    let mut times = String::from("Time:    ");
+    let mut distances = String::from("Distance:");
+
+    for _ in 0..races {
+        let time = rng.range(7, 100);
+        let best = (time / 2) * (time - time / 2);
+        let distance = rng.range(0, best);
+
+        write!(times, " {:>4}", time).unwrap();
+        write!(distances, " {:>4}", distance).unwrap();
+    }
+
+    write!(out, "{}\n{}\n", times, distances)
+}
+
+/// `size` camel card hands with bids.
+pub fn day07(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    for _ in 0..size {
+        let hand = (0..5).map(|_| *rng.choose(&CARDS)).collect::<String>();
+        writeln!(out, "{} {}", hand, rng.range(1, 1001))?;
+    }
+
+    Ok(())
+}
+
+fn node_name(index: usize) -> String {
+    const ALNUM: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    // Never end in 'A' or 'Z' so only the chosen nodes are starts and ends.
+    const LAST: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY0123456789";
+
+    [
+        ALNUM[index / (36 * LAST.len()) % 36],
+        ALNUM[index / LAST.len() % 36],
+        LAST[index % LAST.len()],
+    ]
+    .iter()
+    .map(|&b| b as char)
+    .collect()
+}
+
+/// A guide of `size` (at most 300) steps and six ghosts. Every ghost walks a loop whose
+/// length is a multiple of the guide length, reaching its end node exactly once per
+/// loop, which is the shape the real inputs have.
+pub fn day08(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    const LOOPS: [u64; 6] = [2, 3, 5, 7, 11, 13];
+
+    let guide_len = size.clamp(1, 300);
+    let guide = (0..guide_len)
+        .map(|_| if rng.one_in(2) { 'L' } else { 'R' })
+        .collect::<Vec<_>>();
+
+    let mut nodes = vec![];
+    let mut next_name = 0;
+
+    for (ghost, multiple) in LOOPS.iter().enumerate() {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_string(), "ZZZ".to_string())
+        } else {
+            (format!("{0}{0}A", ghost), format!("{0}{0}Z", ghost))
+        };
+
+        let length = guide_len * *multiple as usize;
+        let mut chain = vec![start];
+        for _ in 1..length {
+            chain.push(node_name(next_name));
+            next_name += 1;
+        }
+        chain.push(end);
+
+        for i in 0..length {
+            let next = chain[i + 1].clone();
+            let decoy = chain[rng.index(chain.len())].clone();
+            let (left, right) = if guide[i % guide_len] == 'L' {
+                (next, decoy)
+            } else {
+                (decoy, next)
+            };
+            nodes.push((chain[i].clone(), left, right));
+        }
+
+        // The end node continues around the loop exactly like the start node did.
+        let (_, left, right) = nodes[nodes.len() - length].clone();
+        nodes.push((chain[length].clone(), left, right));
+    }
+
+    rng.shuffle(&mut nodes);
+
+    writeln!(out, "{}\n", guide.iter().collect::<String>())?;
+    for (node, left, right) in nodes {
+        writeln!(out, "{} = ({}, {})", node, left, right)?;
+    }
+
+    Ok(())
+}
+
+/// `size` histories of 21 values, each a polynomial of degree at most six so the
+/// differences always reach zero.
+pub fn day09(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    for _ in 0..size {
+        let degree = rng.range(0, 7) as usize;
+        let coefficients = (0..=degree)
+            .map(|_| rng.range_i64(-10, 11))
+            .collect::<Vec<_>>();
+
+        // Newton form: sum of a_k * C(x, k).
+        let values = (0..21i64)
+            .map(|x| {
+                let mut binomial = 1;
+                let mut value = 0;
+                for (k, a) in coefficients.iter().enumerate() {
+                    value += a * binomial;
+                    binomial = binomial * (x - k as i64) / (k as i64 + 1);
+                }
+                value.to_string()
+            })
+            .collect::<Vec<_>>();
+
+        writeln!(out, "{}", values.join(" "))?;
+    }
+
+    Ok(())
+}
+
+/// A pipe maze around a random region `size` cells wide. The region is made of
+/// overlapping columns so its outline is always a single simple loop. 'S' sits on the
+/// left edge where the loop runs vertically, everything off the loop is junk.
+pub fn day10(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    let width = size.max(2);
+    let height = size.max(2);
+
+    let mut columns: Vec<(usize, usize)> = vec![];
+    for x in 0..width {
+        let column = match columns.last() {
+            None => {
+                let top = rng.index(height - 1);
+                (top, rng.range(top as u64 + 2, height as u64 + 1) as usize)
+            }
+            Some(&(prev_top, prev_bottom)) => {
+                let top = rng.index(prev_bottom);
+                let low = top.max(prev_top) + 1;
+                (top, rng.range(low as u64, height as u64 + 1) as usize)
+            }
+        };
+        debug_assert!(x == 0 || column.0 < columns[x - 1].1);
+        columns.push(column);
+    }
+
+    // Lattice point -> (north, south, east, west) connections.
+    let mut links: BTreeMap<(usize, usize), [bool; 4]> = BTreeMap::new();
+    fn vertical(links: &mut BTreeMap<(usize, usize), [bool; 4]>, x: usize, y1: usize, y2: usize) {
+        for y in y1.min(y2)..y1.max(y2) {
+            links.entry((x, y)).or_default()[1] = true;
+            links.entry((x, y + 1)).or_default()[0] = true;
+        }
+    }
+
+    for (x, &(top, bottom)) in columns.iter().enumerate() {
+        for y in [top, bottom] {
+            links.entry((x, y)).or_default()[2] = true;
+            links.entry((x + 1, y)).or_default()[3] = true;
+        }
+
+        match x.checked_sub(1).map(|prev| columns[prev]) {
+            None => vertical(&mut links, 0, top, bottom),
+            Some((prev_top, prev_bottom)) => {
+                vertical(&mut links, x, prev_top, top);
+                vertical(&mut links, x, prev_bottom, bottom);
+            }
+        }
+    }
+    let (last_top, last_bottom) = columns[width - 1];
+    vertical(&mut links, width, last_top, last_bottom);
+
+    let start = (0, columns[0].0 + 1);
+
+    // Leave a junk margin around the loop.
+    for y in 0..height + 3 {
+        let mut row = String::with_capacity(width + 3);
+        for x in 0..width + 3 {
+            let link = (x > 0 && y > 0)
+                .then(|| links.get(&(x - 1, y - 1)))
+                .flatten();
+
+            let c = match link {
+                _ if (x, y) == (start.0 + 1, start.1 + 1) => 'S',
+                Some([true, true, false, false]) => '|',
+                Some([false, false, true, true]) => '-',
+                Some([true, false, true, false]) => 'L',
+                Some([true, false, false, true]) => 'J',
+                Some([false, true, false, true]) => '7',
+                Some([false, true, true, false]) => 'F',
+                Some(other) => unreachable!("broken loop at {:?}: {:?}", (x, y), other),
+                None if rng.one_in(2) => '.',
+                None => *rng.choose(&['|', '-', 'L', 'J', '7', 'F']),
+            };
+            row.push(c);
+        }
+        writeln!(out, "{}", row)?;
+    }
+
+    Ok(())
+}
+
+/// A `size` x `size` image with sparse galaxies and some guaranteed empty rows and
+/// columns.
+pub fn day11(rng: &mut Rng, size: usize, out: &mut impl Write) -> io::Result<()> {
+    let size = size.max(2);
+    let empty_rows = (0..size / 10)
+        .map(|_| rng.index(size))
+        .collect::<BTreeSet<_>>();
+    let empty_cols = (0..size / 10)
+        .map(|_| rng.index(size))
+        .collect::<BTreeSet<_>>();
+
+    // Rows are written as they are made, so only one is ever held.
+    for y in 0..size {
+        let row = (0..size)
+            .map(|x| {
+                let galaxy = !empty_rows.contains(&y) && !empty_cols.contains(&x) && rng.one_in(40);
+                // Always have a pair to measure.
+                if galaxy || (x, y) == (0, 0) || (x, y) == (size - 1, size - 1) {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(out, "{}", row)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_is_deterministic() {
+    for day in 1..=11 {
+        let a = generate(day, 42, 20).unwrap();
+        let b = generate(day, 42, 20).unwrap();
+        let c = generate(day, 43, 20).unwrap();
+
+        assert_eq!(a, b, "day {}", day);
+        assert_ne!(a, c, "day {}", day);
+        assert!(a.ends_with('\n'), "day {}", day);
+    }
+
+    assert!(generate(12, 0, 1).is_err());
+}
+
+#[test]
+fn test_generated_input() {
+    let lines = |day, size| {
+        let input = generate(day, 7, size).unwrap();
+        input.lines().map(|s| s.to_string()).collect::<Vec<_>>()
+    };
+    let numbers = |s: &str| {
+        s.split_whitespace()
+            .map(|n| n.parse::<i64>().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let day01 = lines(1, 200);
+    assert_eq!(day01.len(), 200);
+    assert!(day01.iter().all(|l| l.chars().any(|c| c.is_ascii_digit())));
+
+    for (i, line) in lines(2, 200).iter().enumerate() {
+        let (game, hands) = line.split_once(": ").unwrap();
+        assert_eq!(game, format!("Game {}", i + 1));
+        for hand in hands.split("; ") {
+            let colors = hand
+                .split(", ")
+                .map(|cube| cube.split_once(' ').unwrap().1)
+                .collect::<BTreeSet<_>>();
+            assert!(!colors.is_empty() && colors.len() == hand.split(", ").count());
+            assert!(colors.iter().all(|color| COLORS.contains(color)));
+        }
+    }
+
+    let day03 = lines(3, 50);
+    assert_eq!(day03.len(), 50);
+    assert!(day03.iter().all(|row| row.len() == 50));
+    assert!(day03.concat().contains(|c: char| c.is_ascii_digit()));
+
+    for (i, line) in lines(4, 200).iter().enumerate() {
+        let (card, rest) = line.split_once(": ").unwrap();
+        let (winning, have) = rest.split_once(" | ").unwrap();
+        assert_eq!(
+            numbers(card.strip_prefix("Card").unwrap()),
+            vec![i as i64 + 1]
+        );
+        assert_eq!(numbers(winning).len(), 10);
+        assert_eq!(numbers(have).len(), 25);
+    }
+
+    let day05 = generate(5, 7, 20).unwrap();
+    let mut sections = day05.split("\n\n");
+    let seeds = sections.next().unwrap().strip_prefix("seeds:").unwrap();
+    assert_eq!(numbers(seeds).len(), 20);
+    for (section, name) in sections.zip(ALMANAC_MAPS) {
+        let mut rows = section.lines();
+        assert_eq!(rows.next(), Some(format!("{} map:", name).as_str()));
+        assert!(rows.all(|row| numbers(row).len() == 3));
+    }
+    assert_eq!(day05.split("\n\n").count(), 8);
+
+    let day06 = lines(6, 4);
+    let times = numbers(day06[0].strip_prefix("Time:").unwrap());
+    let distances = numbers(day06[1].strip_prefix("Distance:").unwrap());
+    assert_eq!(times.len(), 4);
+    for (t, d) in times.iter().zip(distances) {
+        assert!((0..=*t).any(|hold| hold * (t - hold) > d));
+    }
+
+    for line in lines(7, 200) {
+        let (hand, bid) = line.split_once(' ').unwrap();
+        assert!(hand.len() == 5 && hand.chars().all(|c| CARDS.contains(&c)));
+        assert!((1..=1000).contains(&bid.parse::<u32>().unwrap()));
+    }
+
+    let day08 = lines(8, 5);
+    assert!(day08[0].chars().all(|c| c == 'L' || c == 'R'));
+    let nodes = day08[2..]
+        .iter()
+        .map(|line| line.split_once(" = ").unwrap())
+        .collect::<BTreeMap<_, _>>();
+    for next in nodes.values() {
+        let (left, right) = next[1..next.len() - 1].split_once(", ").unwrap();
+        assert!(nodes.contains_key(left) && nodes.contains_key(right));
+    }
+    assert_eq!(nodes.keys().filter(|n| n.ends_with('A')).count(), 6);
+
+    assert!(lines(9, 200).iter().all(|line| numbers(line).len() == 21));
+
+    let day10 = lines(10, 20);
+    assert_eq!(day10.len(), 23);
+    assert!(day10.iter().all(|row| row.len() == 23));
+    assert_eq!(day10.concat().matches('S').count(), 1);
+
+    let day11 = lines(11, 100);
+    assert!(day11.iter().all(|row| row.len() == 100));
+    assert!(day11.concat().matches('#').count() >= 2);
+}
@@ -0,0 +1,143 @@
+//! Lattice polygon geometry. A polygon is its vertices in order, closing back from the
+//! last to the first; repeating the first vertex at the end is allowed.
+
+use std::cmp::Ordering;
+
+use crate::math::gcd;
+
+pub type Point = (i64, i64);
+
+/// Where a point is relative to a polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+/// Twice the area by the shoelace formula, which keeps it an integer.
+pub fn double_area(vertices: &[Point]) -> u128 {
+    let sum = edges(vertices)
+        .map(|((x1, y1), (x2, y2))| x1 as i128 * y2 as i128 - x2 as i128 * y1 as i128)
+        .sum::<i128>();
+
+    sum.unsigned_abs()
+}
+
+/// Lattice points on the edges of the polygon.
+pub fn boundary_points(vertices: &[Point]) -> u128 {
+    edges(vertices)
+        .map(|((x1, y1), (x2, y2))| gcd(x1.abs_diff(x2), y1.abs_diff(y2)) as u128)
+        .sum()
+}
+
+/// Lattice points strictly inside the polygon, by Pick's theorem: A = I + B / 2 - 1.
+pub fn interior_points(vertices: &[Point]) -> u128 {
+    // Rearranged as 2I = 2A - B + 2 to stay in integers.
+    (double_area(vertices) + 2).saturating_sub(boundary_points(vertices)) / 2
+}
+
+/// `a * b` compared with `c * d`. Each factor is a difference of two `i64`s, so a product
+/// can take 129 bits: compare signs, then magnitudes as `u128`.
+fn cmp_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let signed = |x: i128, y: i128| {
+        let magnitude = x.unsigned_abs() * y.unsigned_abs();
+        (magnitude != 0 && (x < 0) != (y < 0), magnitude)
+    };
+
+    match (signed(a, b), signed(c, d)) {
+        ((false, m1), (false, m2)) => m1.cmp(&m2),
+        ((true, m1), (true, m2)) => m2.cmp(&m1),
+        ((true, _), (false, _)) => Ordering::Less,
+        ((false, _), (true, _)) => Ordering::Greater,
+    }
+}
+
+/// Ray casting point-in-polygon test.
+pub fn locate(vertices: &[Point], (px, py): Point) -> Location {
+    let mut inside = false;
+
+    for ((x1, y1), (x2, y2)) in edges(vertices) {
+        // The sign of the cross product of the edge with the vector to the point.
+        let diff = |a: i64, b: i64| a as i128 - b as i128;
+        let cross = cmp_products(diff(x2, x1), diff(py, y1), diff(px, x1), diff(y2, y1));
+        let within = x1.min(x2) <= px && px <= x1.max(x2) && y1.min(y2) <= py && py <= y1.max(y2);
+        if cross == Ordering::Equal && within {
+            return Location::Boundary;
+        }
+
+        // Count edges crossing the ray going right from the point, treating each edge as
+        // half-open in y so a vertex on the ray is only counted once.
+        if (y1 > py) != (y2 > py) {
+            let crosses_right = if y2 > y1 {
+                cross == Ordering::Greater
+            } else {
+                cross == Ordering::Less
+            };
+            if crosses_right {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+#[test]
+fn test_polygon() {
+    let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+    assert_eq!(double_area(&square), 32);
+    assert_eq!(boundary_points(&square), 16);
+    assert_eq!(interior_points(&square), 9);
+
+    // Orientation and a repeated closing vertex don't matter.
+    let closed = [(0, 0), (0, 4), (4, 4), (4, 0), (0, 0)];
+    assert_eq!(double_area(&closed), 32);
+    assert_eq!(interior_points(&closed), 9);
+
+    let triangle = [(0, 0), (6, 0), (0, 3)];
+    assert_eq!(double_area(&triangle), 18);
+    assert_eq!(boundary_points(&triangle), 12);
+    assert_eq!(interior_points(&triangle), 4);
+}
+
+#[test]
+fn test_locate() {
+    let notched = [(0, 0), (4, 0), (4, 4), (2, 2), (0, 4)];
+
+    assert_eq!(locate(&notched, (1, 1)), Location::Inside);
+    assert_eq!(locate(&notched, (2, 3)), Location::Outside);
+    assert_eq!(locate(&notched, (5, 0)), Location::Outside);
+    assert_eq!(locate(&notched, (2, 0)), Location::Boundary);
+    assert_eq!(locate(&notched, (3, 3)), Location::Boundary);
+    assert_eq!(locate(&notched, (4, 4)), Location::Boundary);
+    // The ray from here runs through the notch vertex at (2, 2).
+    assert_eq!(locate(&notched, (1, 2)), Location::Inside);
+}
+
+#[test]
+fn test_locate_extreme() {
+    let (min, max) = (i64::MIN, i64::MAX);
+    let square = [(min, min), (max, min), (max, max), (min, max)];
+    assert_eq!(locate(&square, (0, 0)), Location::Inside);
+    assert_eq!(locate(&square, (min, 0)), Location::Boundary);
+    assert_eq!(locate(&square, (max, max)), Location::Boundary);
+
+    // Above the diagonal is inside, below it outside, and on it is the boundary.
+    let triangle = [(min, min), (max, max), (min, max)];
+    assert_eq!(locate(&triangle, (0, 1)), Location::Inside);
+    assert_eq!(locate(&triangle, (1, 0)), Location::Outside);
+    assert_eq!(locate(&triangle, (5, 5)), Location::Boundary);
+    assert_eq!(locate(&triangle, (max, min)), Location::Outside);
+}
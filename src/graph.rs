@@ -0,0 +1,370 @@
+//! Graph searches over implicit graphs. Nodes are any cloneable, hashable value and
+//! edges come from a `neighbours` function, so grids, state machines and adjacency
+//! maps all work without building a graph structure first.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// Breadth first search from `start`, returning the number of steps to every reachable node.
+pub fn bfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Breadth first search from `start` that stops at the first node satisfying `goal`,
+/// returning the shortest path including both ends.
+pub fn bfs_to<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            return Some(path_to(&parents, node));
+        }
+
+        for next in neighbours(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn path_to<N: Clone + Eq + Hash>(parents: &HashMap<N, Option<N>>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(Some(parent)) = parents.get(&path[path.len() - 1]) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Depth first search from `start`, returning nodes in the order they were first visited.
+/// Neighbours are explored in the order `neighbours` yields them.
+pub fn dfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> Vec<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::from([start.clone()]);
+    let mut stack = vec![neighbours(&start).into_iter()];
+    let mut order = vec![start];
+
+    while let Some(edges) = stack.last_mut() {
+        let Some(next) = edges.next() else {
+            stack.pop();
+            continue;
+        };
+
+        if visited.insert(next.clone()) {
+            stack.push(neighbours(&next).into_iter());
+            order.push(next);
+        }
+    }
+
+    order
+}
+
+/// Cheapest path from `start` to a node satisfying `goal`, where `neighbours` yields
+/// `(node, cost)` pairs. Returns the path including both ends and its total cost.
+pub fn dijkstra<N, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, u64)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, neighbours, |_| 0, goal)
+}
+
+/// Like `dijkstra`, guided by `heuristic`. The heuristic must never overestimate the
+/// remaining cost, otherwise the path found may not be the cheapest.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, u64)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    // Nodes are numbered as they are discovered so the heap doesn't need `N: Ord`.
+    let mut index = HashMap::from([(start.clone(), 0)]);
+    let mut nodes = vec![start];
+    let mut costs = vec![0u64];
+    let mut parents: Vec<Option<usize>> = vec![None];
+
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&nodes[0]), 0u64, 0usize))]);
+
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        // A cheaper way here was found after this entry was pushed.
+        if cost > costs[i] {
+            continue;
+        }
+
+        if goal(&nodes[i]) {
+            let mut path = vec![nodes[i].clone()];
+            let mut current = i;
+            while let Some(parent) = parents[current] {
+                path.push(nodes[parent].clone());
+                current = parent;
+            }
+            path.reverse();
+            return Some((path, cost));
+        }
+
+        for (next, weight) in neighbours(&nodes[i]) {
+            let next_cost = cost.saturating_add(weight);
+
+            let j = match index.get(&next) {
+                Some(&j) if next_cost >= costs[j] => continue,
+                Some(&j) => j,
+                None => {
+                    index.insert(next.clone(), nodes.len());
+                    nodes.push(next);
+                    costs.push(u64::MAX);
+                    parents.push(None);
+                    nodes.len() - 1
+                }
+            };
+
+            costs[j] = next_cost;
+            parents[j] = Some(i);
+            let estimate = next_cost.saturating_add(heuristic(&nodes[j]));
+            heap.push(Reverse((estimate, next_cost, j)));
+        }
+    }
+
+    None
+}
+
+/// Group `nodes` into connected components. Edges are assumed to go both ways.
+pub fn connected_components<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut components = vec![];
+
+    for node in nodes {
+        if seen.contains(&node) {
+            continue;
+        }
+
+        let component = dfs(node, &mut neighbours);
+        seen.extend(component.iter().cloned());
+        components.push(component);
+    }
+
+    components
+}
+
+/// Order `nodes`, and everything reachable from them, so every node comes before its
+/// neighbours. Returns `None` if there is a cycle.
+pub fn topological_sort<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut done = HashSet::new();
+    let mut order = vec![];
+
+    for root in nodes {
+        if done.contains(&root) {
+            continue;
+        }
+
+        let mut on_path = HashSet::from([root.clone()]);
+        let mut stack = vec![neighbours(&root).into_iter()];
+        let mut path = vec![root];
+
+        while let Some(edges) = stack.last_mut() {
+            match edges.next() {
+                Some(next) if on_path.contains(&next) => return None,
+                Some(next) if done.contains(&next) => {}
+                Some(next) => {
+                    on_path.insert(next.clone());
+                    stack.push(neighbours(&next).into_iter());
+                    path.push(next);
+                }
+                None => {
+                    stack.pop();
+                    let node = path.pop()?;
+                    on_path.remove(&node);
+                    done.insert(node.clone());
+                    order.push(node);
+                }
+            }
+        }
+    }
+
+    // Nodes were finished after everything they lead to.
+    order.reverse();
+    Some(order)
+}
+
+/// Find a cycle reachable from `start`, returned with its first node repeated at the end.
+/// Edges are directed: in an undirected graph every edge is a cycle of two.
+pub fn find_cycle<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::from([start.clone()]);
+    let mut on_path = HashMap::from([(start.clone(), 0)]);
+    let mut stack = vec![neighbours(&start).into_iter()];
+    let mut path = vec![start];
+
+    while let Some(edges) = stack.last_mut() {
+        let Some(next) = edges.next() else {
+            stack.pop();
+            let node = path.pop()?;
+            on_path.remove(&node);
+            continue;
+        };
+
+        if let Some(&i) = on_path.get(&next) {
+            let mut cycle = path.split_off(i);
+            cycle.push(next);
+            return Some(cycle);
+        }
+
+        if visited.insert(next.clone()) {
+            on_path.insert(next.clone(), path.len());
+            stack.push(neighbours(&next).into_iter());
+            path.push(next);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+fn grid<'a>(rows: &'a [&str]) -> impl Fn(&(usize, usize)) -> Vec<(usize, usize)> + 'a {
+    move |&(x, y)| {
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|&(x, y)| rows.get(y).and_then(|row| row.as_bytes().get(x)) == Some(&b'.'))
+        .collect()
+    }
+}
+
+#[test]
+fn test_searches() {
+    let rows = ["...#", ".#..", "...#", "##.."];
+    let open = grid(&rows);
+
+    let distances = bfs((0, 0), &open);
+    assert_eq!(distances[&(3, 3)], 6);
+    assert_eq!(distances[&(3, 1)], 4);
+    assert!(!distances.contains_key(&(3, 0)));
+
+    let path = bfs_to((0, 0), &open, |&p| p == (3, 3)).unwrap();
+    assert_eq!(path.len(), 7);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&(3, 3)));
+    assert_eq!(bfs_to((0, 0), &open, |&p| p == (3, 0)), None);
+
+    let order = dfs((0, 0), &open);
+    assert_eq!(order.len(), distances.len());
+    assert_eq!(order[..3], [(0, 0), (1, 0), (2, 0)]);
+}
+
+#[test]
+fn test_weighted_searches() {
+    // Going straight costs 10, the detour through b and c costs 3.
+    let edges = HashMap::from([
+        ('a', vec![('d', 10), ('b', 1)]),
+        ('b', vec![('c', 1)]),
+        ('c', vec![('d', 1)]),
+        ('d', vec![]),
+    ]);
+    let neighbours = |n: &char| edges[n].clone();
+
+    assert_eq!(
+        dijkstra('a', neighbours, |&n| n == 'd'),
+        Some((vec!['a', 'b', 'c', 'd'], 3))
+    );
+    assert_eq!(dijkstra('d', neighbours, |&n| n == 'a'), None);
+
+    let rows = ["....", ".##.", "....", "...."];
+    let open = grid(&rows);
+    let weighted = |p: &(usize, usize)| open(p).into_iter().map(|n| (n, 1));
+    let manhattan = |&(x, y): &(usize, usize)| (x.abs_diff(3) + y.abs_diff(3)) as u64;
+
+    let (path, cost) = astar((0, 0), weighted, manhattan, |&p| p == (3, 3)).unwrap();
+    assert_eq!(cost, 6);
+    assert_eq!(path.len(), 7);
+}
+
+#[test]
+fn test_structure() {
+    let rows = ["..#.", "..#.", "##..", "...."];
+    let open = grid(&rows);
+    let cells = (0..4)
+        .flat_map(|y| (0..4).map(move |x| (x, y)))
+        .filter(|&(x, y)| rows[y].as_bytes()[x] == b'.');
+
+    let mut sizes = connected_components(cells, &open)
+        .iter()
+        .map(|c| c.len())
+        .collect::<Vec<_>>();
+    sizes.sort();
+    assert_eq!(sizes, vec![4, 8]);
+
+    let edges = HashMap::from([(1, vec![2, 3]), (2, vec![4]), (3, vec![4]), (4, vec![])]);
+    let order = topological_sort([4, 3, 2, 1], |n| edges[n].clone()).unwrap();
+    let position = |n| order.iter().position(|&m| m == n).unwrap();
+    assert!(position(1) < position(2) && position(1) < position(3));
+    assert!(position(2) < position(4) && position(3) < position(4));
+    assert_eq!(find_cycle(1, |n| edges[n].clone()), None);
+
+    let edges = HashMap::from([(1, vec![2]), (2, vec![3]), (3, vec![4, 2]), (4, vec![])]);
+    assert_eq!(topological_sort([1], |n| edges[n].clone()), None);
+    assert_eq!(find_cycle(1, |n| edges[n].clone()), Some(vec![2, 3, 2]));
+}
@@ -1,3 +1,79 @@
-fn main() {
-    println!("Hello, world!");
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use common::store;
+
+const USAGE: &str = "usage: advent-of-code-2023 generate <day> [--seed S] [--size K]
+       advent-of-code-2023 encrypt [dayNN...]
+       advent-of-code-2023 decrypt [dayNN...]";
+
+fn generate(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let day = args.first().ok_or(USAGE)?.parse::<u32>()?;
+
+    let mut seed = 0;
+    let mut size = 100;
+
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        let value = flags
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+
+        match flag.as_str() {
+            "--seed" => seed = value.parse()?,
+            "--size" => size = value.parse()?,
+            _ => return Err(format!("Unknown flag: {}\n{}", flag, USAGE).into()),
+        }
+    }
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    common::generate::write(day, seed, size, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+type Convert = fn(&[u8; 32], &str) -> Result<PathBuf, Box<dyn Error>>;
+
+/// Encrypt or decrypt the named days, or every day that has a file to convert. The
+/// source files are kept, delete them once the output has been checked.
+fn migrate(
+    args: &[String],
+    source: fn(&str) -> PathBuf,
+    convert: Convert,
+) -> Result<(), Box<dyn Error>> {
+    let key = store::key_from_env()?;
+
+    let days = if args.is_empty() {
+        (1..=25)
+            .map(|day| format!("day{:02}", day))
+            .filter(|day| source(day).exists())
+            .collect()
+    } else {
+        args.to_vec()
+    };
+    if days.is_empty() {
+        return Err("No input files found".into());
+    }
+
+    for day in days {
+        let written = convert(&key, &day)?;
+        eprintln!("{} -> {}", source(&day).display(), written.display());
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(|s| s.as_str()) {
+        Some("generate") => generate(&args[1..]),
+        Some("encrypt") => migrate(&args[1..], common::input_path, store::encrypt_file),
+        Some("decrypt") => migrate(&args[1..], store::encrypted_path, store::decrypt_file),
+        _ => Err(USAGE.into()),
+    }
 }
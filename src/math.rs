@@ -0,0 +1,120 @@
+//! Number theory for cycle alignment puzzles. Everything that can overflow returns an
+//! `Option` rather than wrapping.
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y == g`, where `g` is the gcd of `a` and `b`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Least common multiple of all of `numbers`, `None` if it doesn't fit in a `u64`. The
+/// lcm of nothing is 1, and of anything including 0 is 0.
+pub fn lcm(numbers: &[u64]) -> Option<u64> {
+    numbers.iter().try_fold(1u64, |lcm, &n| {
+        if lcm == 0 || n == 0 {
+            return Some(0);
+        }
+        (lcm / gcd(lcm, n)).checked_mul(n)
+    })
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, if there is one.
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a as i128, m as i128);
+    (g == 1).then(|| x.rem_euclid(m as i128) as u64)
+}
+
+/// Solve a system of congruences `x ≡ residue (mod modulus)`, given as
+/// `(residue, modulus)` pairs. The moduli don't need to be coprime and residues may be
+/// any offset, they are reduced first. Returns `(x, m)` meaning every solution is
+/// `x + k * m`, with `x` the smallest non-negative one, or `None` if the congruences
+/// contradict each other or `m` doesn't fit in a `u64`.
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let (mut x, mut m) = (0u128, 1u128);
+
+    for &(residue, modulus) in congruences {
+        if modulus == 0 {
+            return None;
+        }
+        let (a, n) = ((residue % modulus) as u128, modulus as u128);
+
+        // Need x + m * k ≡ a (mod n), solvable only if the gcd divides the difference.
+        let g = gcd(m as u64, modulus) as u128;
+        let diff = (a + n - x % n) % n;
+        if diff % g != 0 {
+            return None;
+        }
+
+        let reduced = n / g;
+        let inverse = mod_inverse(((m / g) % reduced) as u64, reduced as u64)? as u128;
+        // Both factors are below 2^64 so the product fits.
+        let k = (diff / g % reduced) * inverse % reduced;
+
+        let next_m = m / g * n;
+        if next_m > u64::MAX as u128 {
+            return None;
+        }
+        x = (x + m * k) % next_m;
+        m = next_m;
+    }
+
+    Some((x as u64, m as u64))
+}
+
+#[test]
+fn test_gcd_and_lcm() {
+    assert_eq!(gcd(12, 18), 6);
+    assert_eq!(gcd(0, 5), 5);
+
+    let (g, x, y) = extended_gcd(240, 46);
+    assert_eq!(g, 2);
+    assert_eq!(240 * x + 46 * y, 2);
+
+    assert_eq!(lcm(&[4, 6, 10]), Some(60));
+    assert_eq!(lcm(&[]), Some(1));
+    assert_eq!(lcm(&[3, 0]), Some(0));
+    assert_eq!(lcm(&[u64::MAX, u64::MAX - 1]), None);
+
+    assert_eq!(mod_inverse(3, 11), Some(4));
+    assert_eq!(mod_inverse(6, 9), None);
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt(&[]), Some((0, 1)));
+
+    // Moduli sharing a factor, consistent and not.
+    assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+    assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+
+    // Offsets past the modulus are reduced.
+    assert_eq!(crt(&[(13, 4), (17, 6)]), Some((5, 12)));
+
+    assert_eq!(crt(&[(0, u64::MAX), (1, u64::MAX - 1)]), None);
+}
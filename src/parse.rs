@@ -0,0 +1,326 @@
+//! Small parsing helpers for the shapes puzzle inputs keep coming in: integer lists,
+//! `key: value` lines, `Label N:` prefixes and section headers. Failures say where in the
+//! line things went wrong.
+
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number, when known.
+    pub line: Option<usize>,
+    /// Byte offset into the text being parsed.
+    pub pos: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(pos: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: None,
+            pos,
+            message: message.into(),
+        }
+    }
+
+    /// Attach the 1-based line number the failing text came from.
+    pub fn on_line(self, line: usize) -> ParseError {
+        ParseError {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    /// Move an error from parsing `part`, a slice borrowed from `text`, to where `part`
+    /// sits in `text`.
+    pub fn within(self, text: &str, part: &str) -> ParseError {
+        let offset = (part.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|offset| offset + part.len() <= text.len())
+            .expect("part must be a slice of text");
+
+        ParseError {
+            pos: self.pos + offset,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        write!(f, "column {}: {}", self.pos + 1, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A position in some text that parsers advance through. Every method that fails leaves
+/// the cursor where it was, so alternatives can be tried with `optional`.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Cursor<'a> {
+        Cursor { input, pos: 0 }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.pos, message)
+    }
+
+    /// Skip any whitespace.
+    pub fn ws(&mut self) -> &mut Self {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+        self
+    }
+
+    /// Consume exactly `tag`.
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if !self.rest().starts_with(tag) {
+            return Err(self.error(format!("expected {:?}", tag)));
+        }
+        self.pos += tag.len();
+        Ok(())
+    }
+
+    /// Consume a run of letters, digits and underscores.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a word"));
+        }
+
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Consume an integer with an optional leading `-` or `+`.
+    pub fn int<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with(['-', '+']));
+        let digits = rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - sign);
+        if digits == 0 {
+            return Err(self.error("expected an integer"));
+        }
+
+        let text = &rest[..sign + digits];
+        let n = text
+            .parse()
+            .map_err(|_| self.error(format!("{} is out of range", text)))?;
+        self.pos += text.len();
+        Ok(n)
+    }
+
+    /// Fail unless everything has been consumed.
+    pub fn end(&self) -> Result<(), ParseError> {
+        match self.rest().chars().next() {
+            Some(c) => Err(self.error(format!("unexpected {:?}", c))),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `parse`, putting the cursor back and returning `None` if it fails.
+    pub fn optional<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Option<T> {
+        let start = *self;
+        parse(self).map_err(|_| *self = start).ok()
+    }
+
+    /// One or more `item`s separated by `sep`, with whitespace allowed around separators.
+    pub fn list<T>(
+        &mut self,
+        sep: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let start = *self;
+        let mut items = vec![];
+
+        loop {
+            match item(self) {
+                Ok(value) => items.push(value),
+                Err(e) => {
+                    *self = start;
+                    return Err(e);
+                }
+            }
+
+            let before_sep = *self;
+            if self.optional(|c| c.ws().tag(sep)).is_none() {
+                return Ok(items);
+            }
+            self.ws();
+
+            // A trailing separator isn't part of the list.
+            if self.is_empty() {
+                *self = before_sep;
+                return Ok(items);
+            }
+        }
+    }
+}
+
+/// Parse all of `text` as whitespace separated integers.
+pub fn ints<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let mut cursor = Cursor::new(text);
+    let mut out = vec![];
+
+    while !cursor.ws().is_empty() {
+        out.push(cursor.int()?);
+        if !cursor.is_empty() && !cursor.rest().starts_with(char::is_whitespace) {
+            return Err(cursor.error("expected whitespace between integers"));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Every integer that appears anywhere in `text`, ignoring whatever is between them. A
+/// `-` directly before digits makes the number negative.
+pub fn extract_ints<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let mut cursor = Cursor::new(text);
+    let mut out = vec![];
+
+    while let Some(c) = cursor.rest().chars().next() {
+        let starts_number = c.is_ascii_digit()
+            || (c == '-' && cursor.rest()[1..].starts_with(|c: char| c.is_ascii_digit()));
+
+        if starts_number {
+            out.push(cursor.int()?);
+        } else {
+            cursor.pos += c.len_utf8();
+        }
+    }
+
+    Ok(out)
+}
+
+/// Split `key: value` at the first colon, trimming both sides.
+pub fn key_value(line: &str) -> Result<(&str, &str), ParseError> {
+    let (key, value) = line
+        .split_once(':')
+        .ok_or_else(|| ParseError::new(line.len(), "expected \":\""))?;
+
+    Ok((key.trim(), value.trim()))
+}
+
+/// Split a `Label N: rest` line into its label, number and the trimmed rest.
+pub fn labelled<T: FromStr>(line: &str) -> Result<(&str, T, &str), ParseError> {
+    let mut cursor = Cursor::new(line);
+
+    let label = cursor.ws().word()?;
+    if !cursor.rest().starts_with(char::is_whitespace) {
+        return Err(cursor.error("expected a space after the label"));
+    }
+    let n = cursor.ws().int()?;
+    cursor.ws().tag(":")?;
+
+    Ok((label, n, cursor.rest().trim()))
+}
+
+/// Split a section into its header line, without the trailing colon, and the lines after.
+pub fn section(text: &str) -> Result<(&str, &str), ParseError> {
+    let text = text.trim_start_matches('\n');
+    let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+
+    let name = header.trim_end().strip_suffix(':').ok_or_else(|| {
+        ParseError::new(header.len(), "expected a section header ending in \":\"")
+    })?;
+
+    Ok((name.trim(), body))
+}
+
+#[test]
+fn test_helpers() {
+    assert_eq!(ints::<i64>(" 1 -2  +3\t40 "), Ok(vec![1, -2, 3, 40]));
+    assert_eq!(ints::<u32>(""), Ok(vec![]));
+    assert_eq!(ints::<u8>("1 2 x").unwrap_err().pos, 4);
+    assert_eq!(
+        ints::<u8>("1 300").unwrap_err().to_string(),
+        "column 3: 300 is out of range"
+    );
+    assert_eq!(ints::<u8>("1 2x").unwrap_err().pos, 3);
+
+    assert_eq!(
+        extract_ints::<i32>("Time: 7 15, x=-3 and 4-5"),
+        Ok(vec![7, 15, -3, 4, -5])
+    );
+
+    assert_eq!(key_value("Time:  7 15 "), Ok(("Time", "7 15")));
+    assert!(key_value("no colon").is_err());
+
+    assert_eq!(
+        labelled::<u32>("Card  12: 1 2 | 3"),
+        Ok(("Card", 12, "1 2 | 3"))
+    );
+    assert_eq!(labelled::<u32>("Game x: 1").unwrap_err().pos, 5);
+    assert_eq!(labelled::<u32>("Game1: 1").unwrap_err().pos, 5);
+
+    assert_eq!(
+        section("a-to-b map:\n1 2 3\n"),
+        Ok(("a-to-b map", "1 2 3\n"))
+    );
+    let err = section("1 2 3\n").unwrap_err().on_line(4);
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 6: expected a section header ending in \":\""
+    );
+}
+
+#[test]
+fn test_within() {
+    let line = "Card 1: 1 2 | 3 x";
+    let (_, have) = line.split_once('|').unwrap();
+
+    let err = ints::<u32>(have).unwrap_err();
+    assert_eq!(err.pos, 3);
+    assert_eq!(
+        err.within(line, have).on_line(2).to_string(),
+        "line 2, column 17: expected an integer"
+    );
+}
+
+#[test]
+fn test_cursor() {
+    let mut cursor = Cursor::new("AAA = (BBB, CCC) extra");
+
+    assert_eq!(cursor.word(), Ok("AAA"));
+    cursor.ws().tag("=").unwrap();
+    assert!(cursor.tag("(").is_err());
+    cursor.ws().tag("(").unwrap();
+
+    let nodes = cursor.list(",", |c| c.word()).unwrap();
+    assert_eq!(nodes, vec!["BBB", "CCC"]);
+    cursor.tag(")").unwrap();
+
+    assert_eq!(cursor.optional(|c| c.ws().int::<u32>()), None);
+    assert_eq!(cursor.pos(), 16);
+    assert_eq!(
+        cursor.end().unwrap_err().to_string(),
+        "column 17: unexpected ' '"
+    );
+}
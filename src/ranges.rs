@@ -0,0 +1,331 @@
+//! Half-open intervals, sets of them and piecewise-linear maps between them, so range
+//! heavy puzzles can move whole intervals around instead of single values.
+
+use std::cmp::Ordering;
+
+/// The values `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Interval {
+    /// An `end` before `start` gives an empty interval.
+    pub fn new(start: u64, end: u64) -> Interval {
+        Interval {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    /// The `len` values from `start`, or `None` if they run past `u64::MAX`.
+    pub fn from_len(start: u64, len: u64) -> Option<Interval> {
+        Some(Interval::new(start, start.checked_add(len)?))
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, n: u64) -> bool {
+        self.start <= n && n < self.end
+    }
+
+    /// The overlap of two intervals, `None` if they don't share a value.
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start < end).then_some(Interval { start, end })
+    }
+}
+
+/// A set of values stored as sorted, disjoint, non-adjacent, non-empty intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    intervals: Vec<Interval>,
+}
+
+impl FromIterator<Interval> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut sorted = iter
+            .into_iter()
+            .filter(|i| !i.is_empty())
+            .collect::<Vec<_>>();
+        sorted.sort();
+
+        let mut intervals: Vec<Interval> = Vec::with_capacity(sorted.len());
+        for interval in sorted {
+            match intervals.last_mut() {
+                // Overlapping or touching, grow the previous one.
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+
+        RangeSet { intervals }
+    }
+}
+
+impl From<Interval> for RangeSet {
+    fn from(interval: Interval) -> Self {
+        RangeSet::from_iter([interval])
+    }
+}
+
+impl RangeSet {
+    pub fn new() -> RangeSet {
+        RangeSet::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many values are in the set. A `u128` as every `u64` fits.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(|i| i.len() as u128).sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn contains(&self, n: u64) -> bool {
+        self.find(n).is_some()
+    }
+
+    fn find(&self, n: u64) -> Option<&Interval> {
+        self.intervals
+            .binary_search_by(|i| {
+                if i.end <= n {
+                    Ordering::Less
+                } else if i.start > n {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .ok()
+            .map(|index| &self.intervals[index])
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        *self = self.union(&RangeSet::from(interval));
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        self.intervals
+            .iter()
+            .chain(&other.intervals)
+            .copied()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut out = vec![];
+        let (mut a, mut b) = (0, 0);
+
+        while let (Some(x), Some(y)) = (self.intervals.get(a), other.intervals.get(b)) {
+            out.extend(x.intersection(y));
+
+            // Whichever finishes first can't overlap anything further along.
+            if x.end < y.end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+
+        RangeSet { intervals: out }
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut out = vec![];
+        let mut holes = other.intervals.iter().peekable();
+
+        for interval in &self.intervals {
+            let mut start = interval.start;
+
+            // Skip holes entirely before this interval, they can't affect later ones either.
+            while holes.next_if(|hole| hole.end <= start).is_some() {}
+
+            for hole in holes.clone() {
+                if hole.start >= interval.end {
+                    break;
+                }
+                if start < hole.start {
+                    out.push(Interval::new(start, hole.start));
+                }
+                start = start.max(hole.end);
+            }
+
+            if start < interval.end {
+                out.push(Interval::new(start, interval.end));
+            }
+        }
+
+        RangeSet { intervals: out }
+    }
+
+    /// The intervals of the set, cut wherever one of `points` falls strictly inside one.
+    pub fn split(&self, points: impl IntoIterator<Item = u64>) -> Vec<Interval> {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+        points.sort_unstable();
+        points.dedup();
+
+        let mut out = vec![];
+        for interval in &self.intervals {
+            let first = points.partition_point(|&p| p <= interval.start);
+
+            let mut start = interval.start;
+            for &point in points[first..].iter().take_while(|&&p| p < interval.end) {
+                out.push(Interval::new(start, point));
+                start = point;
+            }
+            out.push(Interval::new(start, interval.end));
+        }
+
+        out
+    }
+}
+
+/// A piecewise-linear map: values inside a source interval are shifted to the matching
+/// place in its destination, everything else maps to itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// `(source, destination start)`, sorted by source and never overlapping.
+    segments: Vec<(Interval, u64)>,
+}
+
+impl RangeMap {
+    pub fn new() -> RangeMap {
+        RangeMap::default()
+    }
+
+    /// Send `source` to the interval of the same length starting at `dest`. Returns `None`
+    /// if `source` overlaps an earlier insert or the destination would run past
+    /// `u64::MAX`.
+    pub fn insert(&mut self, source: Interval, dest: u64) -> Option<()> {
+        dest.checked_add(source.len())?;
+        if source.is_empty() {
+            return Some(());
+        }
+
+        let index = self
+            .segments
+            .partition_point(|(s, _)| s.end <= source.start);
+        if let Some((next, _)) = self.segments.get(index) {
+            if next.start < source.end {
+                return None;
+            }
+        }
+        self.segments.insert(index, (source, dest));
+
+        Some(())
+    }
+
+    fn segment(&self, n: u64) -> Option<&(Interval, u64)> {
+        let index = self.segments.partition_point(|(s, _)| s.end <= n);
+        self.segments.get(index).filter(|(s, _)| s.contains(n))
+    }
+
+    pub fn get(&self, n: u64) -> u64 {
+        match self.segment(n) {
+            Some(&(source, dest)) => dest + (n - source.start),
+            None => n,
+        }
+    }
+
+    /// Map every value in `set` at once, splitting intervals that straddle a segment edge.
+    pub fn apply(&self, set: &RangeSet) -> RangeSet {
+        let edges = self.segments.iter().flat_map(|(s, _)| [s.start, s.end]);
+
+        set.split(edges)
+            .into_iter()
+            .map(|piece| match self.segment(piece.start) {
+                Some(&(source, dest)) => {
+                    let start = dest + (piece.start - source.start);
+                    Interval::new(start, start + piece.len())
+                }
+                None => piece,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_range_set() {
+    let set = RangeSet::from_iter([
+        Interval::new(10, 20),
+        Interval::new(0, 5),
+        Interval::new(5, 7),
+        Interval::new(15, 25),
+        Interval::new(30, 30),
+    ]);
+    assert_eq!(
+        set.intervals(),
+        [Interval::new(0, 7), Interval::new(10, 25)]
+    );
+    assert_eq!(set.len(), 22);
+    assert!(set.contains(6) && !set.contains(7) && set.contains(24));
+
+    let other = RangeSet::from_iter([Interval::new(3, 12), Interval::new(20, 40)]);
+    assert_eq!(set.union(&other).intervals(), [Interval::new(0, 40)]);
+    assert_eq!(
+        set.intersection(&other).intervals(),
+        [
+            Interval::new(3, 7),
+            Interval::new(10, 12),
+            Interval::new(20, 25)
+        ]
+    );
+    assert_eq!(
+        set.difference(&other).intervals(),
+        [Interval::new(0, 3), Interval::new(12, 20)]
+    );
+    assert_eq!(
+        set.split([3, 10, 12, 40]),
+        [
+            Interval::new(0, 3),
+            Interval::new(3, 7),
+            Interval::new(10, 12),
+            Interval::new(12, 25)
+        ]
+    );
+}
+
+#[test]
+fn test_range_map() {
+    let mut map = RangeMap::new();
+    map.insert(Interval::new(98, 100), 50).unwrap();
+    map.insert(Interval::new(50, 98), 52).unwrap();
+    // Overlapping an earlier insert is rejected and changes nothing.
+    assert_eq!(map.insert(Interval::new(96, 102), 0), None);
+    assert_eq!(map.insert(Interval::new(40, 51), 0), None);
+    assert_eq!(map.insert(Interval::new(0, 2), u64::MAX - 1), None);
+    map.insert(Interval::new(100, 100), 0).unwrap();
+
+    assert_eq!(map.get(49), 49);
+    assert_eq!(map.get(50), 52);
+    assert_eq!(map.get(99), 51);
+    assert_eq!(map.get(101), 101);
+
+    let seeds = RangeSet::from_iter([Interval::new(79, 93), Interval::new(96, 103)]);
+    assert_eq!(
+        map.apply(&seeds).intervals(),
+        [
+            Interval::new(50, 52),
+            Interval::new(81, 95),
+            Interval::new(98, 103)
+        ]
+    );
+}
@@ -0,0 +1,82 @@
+/// Small seeded PRNG (SplitMix64) so generated inputs are reproducible across machines
+/// without pulling in the rand crate.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `low..high`, `high` must be greater than `low`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        let span = high - low;
+        // Multiply-shift keeps the bias negligible without a rejection loop.
+        low + ((self.next_u64() as u128 * span as u128) >> 64) as u64
+    }
+
+    pub fn range_i64(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        let span = high.abs_diff(low);
+        low.wrapping_add(self.range(0, span) as i64)
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0, len as u64) as usize
+    }
+
+    /// True roughly once every `n` calls.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.range(0, n) == 0
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[test]
+fn test_rng_is_deterministic() {
+    let a = (0..10).map(|_| Rng::new(7).next_u64()).collect::<Vec<_>>();
+    assert!(a.iter().all(|&n| n == a[0]));
+
+    let mut rng = Rng::new(7);
+    let b = (0..10).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    assert_eq!(b[0], a[0]);
+    assert_ne!(b[0], b[1]);
+}
+
+#[test]
+fn test_rng_range() {
+    let mut rng = Rng::new(1);
+    for _ in 0..1000 {
+        let n = rng.range(3, 9);
+        assert!((3..9).contains(&n));
+
+        let n = rng.range_i64(-5, 5);
+        assert!((-5..5).contains(&n));
+    }
+
+    let mut items = (0..10).collect::<Vec<_>>();
+    rng.shuffle(&mut items);
+    items.sort();
+    assert_eq!(items, (0..10).collect::<Vec<_>>());
+}
@@ -0,0 +1,154 @@
+//! Feeds mutated puzzle inputs to a day's solver and checks it fails gracefully: an
+//! error or an answer is fine, a panic or a hang is not.
+
+use std::{error::Error, sync::mpsc, thread, time::Duration};
+
+use crate::{generate::generate, rng::Rng};
+
+/// How long a single solve may take before we assume it is looping forever.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+const UNICODE: [&str; 8] = ["é", "€", "😀", "\u{0}", "\u{feff}", "\u{301}", "Ⅻ", "٣"];
+
+const HUGE_NUMBERS: [&str; 6] = [
+    "4294967296",
+    "18446744073709551615",
+    "18446744073709551616",
+    "-9223372036854775808",
+    "99999999999999999999999999999999",
+    "0",
+];
+
+fn char_boundary(input: &str, rng: &mut Rng) -> usize {
+    let mut i = rng.index(input.len() + 1);
+    while !input.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+/// Apply one random structural or byte level change to `input`.
+pub fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut lines = input.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    match rng.range(0, 7) {
+        // Truncate somewhere.
+        0 => input[..char_boundary(input, rng)].to_string(),
+        // Flip a byte, re-validating in case we landed inside a multi-byte char.
+        1 if !input.is_empty() => {
+            let mut bytes = input.as_bytes().to_vec();
+            let i = rng.index(bytes.len());
+            bytes[i] ^= 1 << rng.range(0, 8);
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        // Insert something that isn't ASCII.
+        2 => {
+            let i = char_boundary(input, rng);
+            format!("{}{}{}", &input[..i], rng.choose(&UNICODE), &input[i..])
+        }
+        // Swap a number for one that doesn't fit.
+        3 => {
+            let digits = input
+                .match_indices(|c: char| c.is_ascii_digit())
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let Some(&start) = digits.get(rng.index(digits.len().max(1))) else {
+                return input.to_string();
+            };
+            let end = input[start..]
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(input.len(), |n| start + n);
+
+            format!(
+                "{}{}{}",
+                &input[..start],
+                rng.choose(&HUGE_NUMBERS),
+                &input[end..]
+            )
+        }
+        // Drop a line.
+        4 if !lines.is_empty() => {
+            lines.remove(rng.index(lines.len()));
+            lines.join("\n")
+        }
+        // Duplicate a line.
+        5 if !lines.is_empty() => {
+            let i = rng.index(lines.len());
+            lines.insert(i, lines[i].clone());
+            lines.join("\n")
+        }
+        // Blank out a line.
+        6 if !lines.is_empty() => {
+            let i = rng.index(lines.len());
+            lines[i].clear();
+            lines.join("\n")
+        }
+        // Line mutations on an empty input have nothing to work on.
+        _ => input.to_string(),
+    }
+}
+
+/// Run `solve` on `cases` mutated versions of generated inputs for `day`, reporting the
+/// first input that panics or takes longer than `TIMEOUT`.
+pub fn check<T, F>(day: u32, size: usize, cases: u64, solve: F) -> Result<(), Box<dyn Error>>
+where
+    T: 'static,
+    F: Fn(&str) -> Result<T, Box<dyn Error>> + Copy + Send + 'static,
+{
+    for seed in 0..cases {
+        let mut rng = Rng::new(seed);
+        let mut input = generate(day, seed, size)?;
+        for _ in 0..rng.range(1, 4) {
+            input = mutate(&input, &mut rng);
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let case = input.clone();
+        thread::spawn(move || {
+            // Only the outcome matters, an error is as good as an answer.
+            let _ = solve(&case);
+            let _ = tx.send(());
+        });
+
+        match rx.recv_timeout(TIMEOUT) {
+            Ok(()) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(format!("day {} panicked on seed {}:\n{}", day, seed, input).into());
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                return Err(format!("day {} hung on seed {}:\n{}", day, seed, input).into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_mutate_changes_input() {
+    let input = generate(2, 0, 10).unwrap();
+    let mut rng = Rng::new(0);
+
+    let changed = (0..100)
+        .filter(|_| mutate(&input, &mut rng) != input)
+        .count();
+    assert!(changed > 90);
+
+    // Only a truncation right at the start should leave nothing behind.
+    let empty = (0..100)
+        .filter(|_| mutate(&input, &mut rng).is_empty())
+        .count();
+    assert!(empty < 5);
+}
+
+#[test]
+fn test_check_catches_panics() {
+    let panics = |input: &str| -> Result<u8, Box<dyn Error>> {
+        let first = input.lines().next().ok_or("empty")?;
+        Ok(first.as_bytes()[100])
+    };
+    assert!(check(1, 5, 5, panics).is_err());
+
+    let fine = |input: &str| -> Result<usize, Box<dyn Error>> { Ok(input.len()) };
+    assert!(check(1, 5, 5, fine).is_ok());
+}
@@ -0,0 +1,162 @@
+//! Inputs kept encrypted at rest as `<day>.txt.enc` next to where the plain file would
+//! be, sealed with ChaCha20-Poly1305 under a key from `$AOC_INPUT_KEY` (64 hex digits).
+//!
+//! A file is `MAGIC`, a random nonce, then the sealed input. The day's name is
+//! authenticated along with it so one day's file can't be swapped in for another's.
+//!
+//! Each maintainer provisions their own key, nothing here generates or stores one:
+//!
+//! ```text
+//! export AOC_INPUT_KEY=$(openssl rand -hex 32)   # keep it in a password manager
+//! advent-of-code-2023 encrypt                    # writes input/dayNN.txt.enc
+//! advent-of-code-2023 decrypt                    # restores input/dayNN.txt
+//! ```
+//!
+//! Check that `decrypt` gives back the same files before deleting the plain ones, and
+//! remember they stay in git history until that is rewritten.
+
+use std::{
+    collections::hash_map::RandomState,
+    error::Error,
+    hash::{BuildHasher, Hasher},
+    io,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use crate::cipher::{self, KEY_LEN, NONCE_LEN};
+
+pub const KEY_VAR: &str = "AOC_INPUT_KEY";
+
+const MAGIC: &[u8] = b"aoc-enc1";
+
+/// Where the encrypted copy of a day's input lives.
+pub fn encrypted_path(day: &str) -> PathBuf {
+    let mut path = crate::input_path(day).into_os_string();
+    path.push(".enc");
+    path.into()
+}
+
+pub fn parse_key(hex: &str) -> Result<[u8; KEY_LEN], Box<dyn Error>> {
+    let hex = hex.trim();
+    if hex.len() != KEY_LEN * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("{} must be {} hex digits", KEY_VAR, KEY_LEN * 2).into());
+    }
+
+    let mut key = [0; KEY_LEN];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(key)
+}
+
+pub fn key_from_env() -> Result<[u8; KEY_LEN], Box<dyn Error>> {
+    let hex = std::env::var(KEY_VAR).map_err(|_| format!("{} is not set", KEY_VAR))?;
+    parse_key(&hex)
+}
+
+/// A fresh nonce. std has no randomness API, but every `RandomState` is keyed from the
+/// OS, so hashing the clock through two of them gives 96 unpredictable bits.
+fn nonce() -> [u8; NONCE_LEN] {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+
+    let mut nonce = [0; NONCE_LEN];
+    for chunk in nonce.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(now);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+    nonce
+}
+
+fn aad(day: &str) -> Vec<u8> {
+    [MAGIC, day.as_bytes()].concat()
+}
+
+pub fn encrypt(key: &[u8; KEY_LEN], day: &str, input: &str) -> Vec<u8> {
+    let nonce = nonce();
+
+    let mut out = MAGIC.to_vec();
+    out.extend(nonce);
+    out.extend(cipher::seal(key, &nonce, &aad(day), input.as_bytes()));
+    out
+}
+
+pub fn decrypt(key: &[u8; KEY_LEN], day: &str, data: &[u8]) -> Result<String, Box<dyn Error>> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| format!("{} is not an encrypted input", day))?;
+    if rest.len() < NONCE_LEN {
+        return Err(format!("Encrypted input for {} is truncated", day).into());
+    }
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let nonce = nonce.try_into()?;
+
+    let input = cipher::open(key, &nonce, &aad(day), sealed).ok_or_else(|| {
+        format!(
+            "Could not decrypt {}: wrong key or the file was modified",
+            day
+        )
+    })?;
+    Ok(String::from_utf8(input)?)
+}
+
+/// The decrypted input for `day`, or `None` if there is no encrypted copy.
+pub fn read(day: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let data = match std::fs::read(encrypted_path(day)) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        data => data?,
+    };
+
+    decrypt(&key_from_env()?, day, &data).map(Some)
+}
+
+/// Write the encrypted copy of a day's plain input file. The plain file is left alone.
+pub fn encrypt_file(key: &[u8; KEY_LEN], day: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let input = std::fs::read_to_string(crate::input_path(day))?;
+    let path = encrypted_path(day);
+
+    std::fs::write(&path, encrypt(key, day, &input))?;
+    Ok(path)
+}
+
+/// Write the plain input file back out from a day's encrypted copy.
+pub fn decrypt_file(key: &[u8; KEY_LEN], day: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let data = std::fs::read(encrypted_path(day))?;
+    let path = crate::input_path(day);
+
+    std::fs::write(&path, decrypt(key, day, &data)?)?;
+    Ok(path)
+}
+
+#[test]
+fn test_encrypt_and_decrypt() {
+    let key = parse_key(&"0123456789abcdef".repeat(4)).unwrap();
+    let input = "Time:      7  15   30\nDistance:  9  40  200\n";
+
+    let data = encrypt(&key, "day06", input);
+    assert_eq!(decrypt(&key, "day06", &data).unwrap(), input);
+    // Nonces are fresh each time.
+    assert_ne!(encrypt(&key, "day06", input), data);
+
+    assert!(decrypt(&key, "day07", &data).is_err());
+    let mut other_key = key;
+    other_key[0] ^= 1;
+    assert!(decrypt(&other_key, "day06", &data).is_err());
+    let mut tampered = data.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(decrypt(&key, "day06", &tampered).is_err());
+    assert!(decrypt(&key, "day06", input.as_bytes()).is_err());
+    assert!(decrypt(&key, "day06", &data[..MAGIC.len() + 4]).is_err());
+}
+
+#[test]
+fn test_parse_key() {
+    let key = parse_key(&"00ff".repeat(16)).unwrap();
+    assert_eq!(key[..2], [0x00, 0xff]);
+
+    assert!(parse_key("00ff").is_err());
+    assert!(parse_key(&"0g".repeat(32)).is_err());
+}